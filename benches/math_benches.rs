@@ -1,6 +1,6 @@
 use neofoodclub::math;
 use neofoodclub::modifier::{Modifier, ModifierFlags};
-use neofoodclub::nfc::{NeoFoodClub, ProbabilityModel};
+use neofoodclub::nfc::{BetWeighting, NeoFoodClub, ProbabilityModel};
 
 fn main() {
     divan::main();
@@ -253,6 +253,12 @@ fn bench_make_crazy_bets() {
     divan::black_box(nfc.make_crazy_bets());
 }
 
+#[divan::bench]
+fn bench_make_weighted_crazy_bets() {
+    let nfc = NeoFoodClub::from_json(ROUND_DATA_JSON, Some(BET_AMOUNT), None, None);
+    divan::black_box(nfc.make_weighted_crazy_bets(BetWeighting::NetExpected, 1.0));
+}
+
 #[divan::bench]
 fn bench_make_all_bets() {
     let nfc = NeoFoodClub::from_json(ROUND_DATA_JSON, Some(BET_AMOUNT), None, None);
@@ -4,8 +4,10 @@ use std::ops::{Add, Sub};
 use crate::{
     food_adjustments::{NEGATIVE_FOOD, POSITIVE_FOOD},
     math::binary_to_indices,
+    nfc::NeoFoodClub,
     pirates::Pirate,
     round_data::RoundData,
+    volatility::OddsVolatility,
 };
 
 pub const ARENA_NAMES: [&str; 5] = ["Shipwreck", "Lagoon", "Treasure", "Hidden", "Harpoon"];
@@ -32,6 +34,7 @@ impl Arena {
         let mut odds = 0.;
 
         let use_odds = round_data.customOdds.unwrap_or(round_data.currentOdds);
+        let use_foods = round_data.customFoods.or(round_data.foods);
 
         // the pirate index of the winning pirate
         let winner = round_data.winners.unwrap_or([0; 5])[id as usize];
@@ -43,7 +46,7 @@ impl Arena {
             let mut pfa: Option<u8> = None;
             let mut nfa: Option<i8> = None;
             let mut fa: Option<i8> = None;
-            if let Some(foods) = &round_data.foods {
+            if let Some(foods) = &use_foods {
                 for food in &foods[id as usize] {
                     pfa = Some(
                         pfa.unwrap_or(0)
@@ -78,7 +81,7 @@ impl Arena {
             pirates,
             odds,
             winner,
-            foods: round_data.foods.map(|f| f[id as usize]),
+            foods: use_foods.map(|f| f[id as usize]),
         }
     }
 
@@ -118,6 +121,17 @@ impl Arena {
     pub fn get_pirate_by_index(&self, index: u8) -> Option<&Pirate> {
         self.pirates.get(index as usize)
     }
+
+    /// Returns statistics on how the odds in this arena moved, for a given NFC object.
+    pub fn volatility(&self, nfc: &NeoFoodClub) -> OddsVolatility {
+        let timeline = nfc.odds_timeline();
+        OddsVolatility::new(
+            timeline
+                .changes()
+                .filter(|change| change.arena_index() == self.id as usize),
+            nfc.close_utc(),
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,337 @@
+use rand::Rng;
+
+use crate::{
+    bets::Bets,
+    math::{bet_payout, pirates_binary, BET_AMOUNT_MAX, BET_AMOUNT_MIN},
+    nfc::NeoFoodClub,
+};
+
+/// How much to bet each round of a bankroll simulation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BetAmountPolicy {
+    /// Bet this amount on every bet, capped to what it takes to hit 1 million.
+    /// The stakes are never scaled down, so the bankroll is ruined once it can't cover them.
+    Fixed(u32),
+    /// Stake this fraction of the Kelly-optimal share of the bankroll.
+    /// A fraction of 1.0 is full Kelly, 0.5 is half Kelly, and so on.
+    Kelly(f64),
+    /// Bet this amount on every bet, but stop playing after `streak` busts in a row.
+    /// Like `Fixed`, the bankroll is ruined once it can't cover the stakes.
+    StopAfterBustStreak { amount: u32, streak: u32 },
+}
+
+impl BetAmountPolicy {
+    /// The bet amount the strategy's bets are filled in with.
+    /// Kelly scales the bets down from the maximum to fit the bankroll.
+    fn bet_amount(&self) -> u32 {
+        match *self {
+            BetAmountPolicy::Fixed(amount) => amount,
+            BetAmountPolicy::StopAfterBustStreak { amount, .. } => amount,
+            BetAmountPolicy::Kelly(_) => BET_AMOUNT_MAX,
+        }
+    }
+}
+
+/// A single simulated run of consecutive rounds.
+#[derive(Debug, Clone)]
+pub struct BankrollPath {
+    /// The bankroll before the first round, and after every round played.
+    pub bankrolls: Vec<u64>,
+
+    /// The largest drop from a previous peak, in neopoints.
+    pub max_drawdown: u64,
+
+    /// The round (one-indexed) after which the bankroll had at least doubled, if it did.
+    pub doubled_at: Option<usize>,
+
+    /// The day (one-indexed) on which the bankroll could no longer place the round's bets
+    /// under the policy, if it did. Under `Fixed` and `StopAfterBustStreak` that's when it's
+    /// below the round's total stake, and under `Kelly` when it's below the minimum bet on every bet.
+    pub ruined_at: Option<usize>,
+
+    /// The round (one-indexed) after which the bust streak limit stopped play, if it did.
+    pub stopped_at: Option<usize>,
+}
+
+impl BankrollPath {
+    /// The bankroll at the end of the path.
+    pub fn final_bankroll(&self) -> u64 {
+        *self
+            .bankrolls
+            .last()
+            .expect("Bankrolls should not be empty")
+    }
+}
+
+/// The results of playing a daily strategy over many consecutive rounds, many times over.
+#[derive(Debug, Clone)]
+pub struct BankrollSimulation {
+    /// The bankroll the paths started with.
+    pub starting_bankroll: u64,
+
+    /// Each simulated path.
+    pub paths: Vec<BankrollPath>,
+}
+
+impl BankrollSimulation {
+    /// Simulates `paths` runs of `days` consecutive rounds each.
+    ///
+    /// Each day plays the next round of `rounds`, cycling back to the start when they run out,
+    /// so they can be synthetic rounds or replayed from an archive.
+    /// The strategy picks the bets for a round, and is called once per round
+    /// (for example `|nfc| Some(nfc.make_max_ter_bets())`), returning None skips the round.
+    /// It's given the round with the policy's bet amount set. Under `Kelly`, the amounts it fills in
+    /// are scaled to the Kelly stake, keeping their ratios; the other policies bet them as they are.
+    /// A path stops playing once it's ruined, see `BankrollPath::ruined_at`.
+    /// The winners of each round are sampled from the model.
+    pub fn new<F, R>(
+        rounds: &[NeoFoodClub],
+        strategy: F,
+        policy: BetAmountPolicy,
+        starting_bankroll: u64,
+        days: usize,
+        paths: usize,
+        rng: &mut R,
+    ) -> Self
+    where
+        F: Fn(&NeoFoodClub) -> Option<Bets>,
+        R: Rng + ?Sized,
+    {
+        assert!(!rounds.is_empty(), "At least one round is needed.");
+
+        let daily_bets: Vec<Option<Bets>> = rounds
+            .iter()
+            .map(|nfc| {
+                let mut nfc = nfc.clone();
+                nfc.set_bet_amount(Some(policy.bet_amount()));
+                strategy(&nfc)
+            })
+            .collect();
+
+        let paths = (0..paths)
+            .map(|_| {
+                let mut bankroll = starting_bankroll;
+                let mut bankrolls = Vec::with_capacity(days + 1);
+                bankrolls.push(bankroll);
+
+                let mut peak = bankroll;
+                let mut max_drawdown = 0;
+                let mut doubled_at = None;
+                let mut ruined_at = None;
+                let mut stopped_at = None;
+                let mut bust_streak = 0;
+
+                for day in 0..days {
+                    let round_index = day % rounds.len();
+
+                    if ruined_at.is_none() && stopped_at.is_none() {
+                        if let Some(bets) = &daily_bets[round_index] {
+                            let nfc = &rounds[round_index];
+
+                            match place_bets(nfc, bets, policy, bankroll) {
+                                Placement::Uncovered => ruined_at = Some(day + 1),
+                                Placement::Skipped => {}
+                                Placement::Placed(bets) => {
+                                    let winners = pirates_binary(nfc.sample_winners(rng));
+                                    let (staked, won) = stake_and_winnings(nfc, &bets, winners);
+
+                                    bankroll = bankroll - staked + won;
+
+                                    bust_streak = if won == 0 { bust_streak + 1 } else { 0 };
+                                    if let BetAmountPolicy::StopAfterBustStreak { streak, .. } =
+                                        policy
+                                    {
+                                        if bust_streak >= streak {
+                                            stopped_at = Some(day + 1);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    peak = peak.max(bankroll);
+                    max_drawdown = max_drawdown.max(peak - bankroll);
+                    if doubled_at.is_none() && bankroll >= starting_bankroll * 2 {
+                        doubled_at = Some(day + 1);
+                    }
+
+                    bankrolls.push(bankroll);
+                }
+
+                BankrollPath {
+                    bankrolls,
+                    max_drawdown,
+                    doubled_at,
+                    ruined_at,
+                    stopped_at,
+                }
+            })
+            .collect();
+
+        Self {
+            starting_bankroll,
+            paths,
+        }
+    }
+
+    /// The share of paths that ran out of neopoints.
+    pub fn probability_of_ruin(&self) -> f64 {
+        self.share_of_paths(|path| path.ruined_at.is_some())
+    }
+
+    /// The share of paths that at least doubled their bankroll at some point.
+    pub fn probability_of_doubling(&self) -> f64 {
+        self.share_of_paths(|path| path.doubled_at.is_some())
+    }
+
+    /// The average amount of rounds it took to double, among the paths that did.
+    /// Returns None if no path doubled.
+    pub fn mean_time_to_double(&self) -> Option<f64> {
+        let times: Vec<usize> = self.paths.iter().filter_map(|p| p.doubled_at).collect();
+
+        if times.is_empty() {
+            return None;
+        }
+
+        Some(times.iter().sum::<usize>() as f64 / times.len() as f64)
+    }
+
+    /// The average largest drawdown across paths, in neopoints.
+    pub fn mean_max_drawdown(&self) -> f64 {
+        if self.paths.is_empty() {
+            return 0.0;
+        }
+
+        self.paths
+            .iter()
+            .map(|p| p.max_drawdown as f64)
+            .sum::<f64>()
+            / self.paths.len() as f64
+    }
+
+    /// The average bankroll at the end of the paths.
+    pub fn mean_final_bankroll(&self) -> f64 {
+        if self.paths.is_empty() {
+            return 0.0;
+        }
+
+        self.paths
+            .iter()
+            .map(|p| p.final_bankroll() as f64)
+            .sum::<f64>()
+            / self.paths.len() as f64
+    }
+
+    fn share_of_paths(&self, predicate: impl Fn(&BankrollPath) -> bool) -> f64 {
+        if self.paths.is_empty() {
+            return 0.0;
+        }
+
+        self.paths.iter().filter(|p| predicate(p)).count() as f64 / self.paths.len() as f64
+    }
+}
+
+/// The outcome of sizing a day's bets.
+enum Placement {
+    /// The bets to place, with their amounts.
+    Placed(Bets),
+    /// There's nothing worth betting today.
+    Skipped,
+    /// The bankroll can't cover the bets under the policy.
+    Uncovered,
+}
+
+/// Sizes the day's bets according to the policy, making sure the bankroll can cover them.
+fn place_bets(nfc: &NeoFoodClub, bets: &Bets, policy: BetAmountPolicy, bankroll: u64) -> Placement {
+    if bets.is_empty() {
+        return Placement::Skipped;
+    }
+
+    let mut bets = bets.clone();
+    if bets.bet_amounts.is_none() {
+        bets.fill_bet_amounts_with(nfc, policy.bet_amount());
+    }
+
+    let amounts: Vec<u64> = bets
+        .bet_amounts
+        .iter()
+        .flatten()
+        .map(|a| a.unwrap_or(0) as u64)
+        .collect();
+    let total: u64 = amounts.iter().sum();
+
+    if total == 0 {
+        return Placement::Skipped;
+    }
+
+    let BetAmountPolicy::Kelly(fraction) = policy else {
+        if bankroll < total {
+            return Placement::Uncovered;
+        }
+        return Placement::Placed(bets);
+    };
+
+    let minimum = amounts.len() as u64 * BET_AMOUNT_MIN as u64;
+    if bankroll < minimum {
+        return Placement::Uncovered;
+    }
+
+    // the amounts are capped per bet, so they aren't equal
+    let kelly_fraction = bets.np_odds(nfc).map_or(0.0, |odds| odds.kelly_fraction());
+    let target = ((bankroll as f64 * kelly_fraction * fraction) as u64).min(bankroll);
+
+    if target == total {
+        return Placement::Placed(bets);
+    }
+
+    // keep the ratios between the amounts, so bustproof sets stay bustproof
+    let maxbets: Vec<u32> = bets
+        .array_indices
+        .iter()
+        .map(|&i| nfc.round_dict_data().maxbets[i])
+        .collect();
+
+    let scaled: Vec<Option<u32>> = amounts
+        .iter()
+        .zip(maxbets.iter())
+        .map(|(&amount, &maxbet)| {
+            let scaled = (amount * target / total) as u32;
+            Some(scaled.min(maxbet).clamp(BET_AMOUNT_MIN, BET_AMOUNT_MAX))
+        })
+        .collect();
+
+    let scaled_total: u64 = scaled.iter().flatten().map(|&a| a as u64).sum();
+    if scaled_total > bankroll || target < minimum {
+        return Placement::Skipped;
+    }
+
+    bets.bet_amounts = Some(scaled);
+
+    Placement::Placed(bets)
+}
+
+/// Returns the total amount staked and the neopoints won, for the given winners,
+/// paid at the bets' locked odds if they have any.
+fn stake_and_winnings(nfc: &NeoFoodClub, bets: &Bets, winners_binary: u32) -> (u64, u64) {
+    let data = nfc.round_dict_data();
+    let amounts = bets.bet_amounts.as_deref().unwrap_or_default();
+    let odds = bets.odds_values(nfc);
+
+    bets.array_indices
+        .iter()
+        .zip(amounts.iter())
+        .zip(odds)
+        .fold((0, 0), |(staked, won), ((&array_index, amount), odds)| {
+            let amount = amount.unwrap_or(0);
+            let bet_bin = data.bins[array_index];
+
+            let winnings = if bet_bin & winners_binary == bet_bin {
+                bet_payout(odds, amount) as u64
+            } else {
+                0
+            };
+
+            (staked + amount as u64, won + winnings)
+        })
+}
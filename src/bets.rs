@@ -1,13 +1,17 @@
+use chrono::{DateTime, Utc};
 use comfy_table::Table;
 
 use crate::{
     arena::ARENA_NAMES,
+    dominance::{find_dominated_bets, DominatedBet},
     math::{
-        amounts_hash_to_bet_amounts, bet_amounts_to_amounts_hash, bets_hash_to_bet_binaries,
-        bets_hash_value, binary_to_indices, pirates_binary, BET_AMOUNT_MAX, BET_AMOUNT_MIN,
+        amounts_hash_to_bet_amounts, bet_amounts_to_amounts_hash, bet_payout,
+        bets_hash_to_bet_binaries, bets_hash_value, binary_to_indices, break_even_probability,
+        pirates_binary, BET_AMOUNT_MAX, BET_AMOUNT_MIN,
     },
     nfc::NeoFoodClub,
-    odds::Odds,
+    odds::{NpOdds, Odds},
+    outcome::BetResult,
     pirates::PartialPirateThings,
 };
 
@@ -74,6 +78,9 @@ pub struct Bets {
     pub bet_binaries: Vec<u32>,
     pub bet_amounts: Option<Vec<Option<u32>>>,
     pub odds: Odds,
+    /// The odds each bet was placed at, if they differ from the round's.
+    /// Set with `set_locked_odds` or `lock_odds_at`, which keep `odds` in sync.
+    locked_odds: Option<Vec<u32>>,
 }
 
 impl Bets {
@@ -91,6 +98,7 @@ impl Bets {
             bet_binaries,
             bet_amounts: None,
             odds,
+            locked_odds: None,
         }
     }
 
@@ -169,11 +177,10 @@ impl Bets {
             return vec![];
         };
 
-        self.array_indices
-            .iter()
+        self.expected_return_list(nfc)
+            .into_iter()
             .zip(amounts.iter())
-            .map(|(i, a)| {
-                let er = nfc.round_dict_data().ers[*i];
+            .map(|(er, a)| {
                 let amount = a.unwrap_or(0) as f64;
                 amount.mul_add(er, -amount)
             })
@@ -187,10 +194,17 @@ impl Bets {
 
     /// Returns the expected return of each bet
     pub fn expected_return_list(&self, nfc: &NeoFoodClub) -> Vec<f64> {
-        self.array_indices
-            .iter()
-            .map(|&i| nfc.round_dict_data().ers[i])
-            .collect()
+        let data = nfc.round_dict_data();
+
+        match &self.locked_odds {
+            Some(locked_odds) => self
+                .array_indices
+                .iter()
+                .zip(locked_odds.iter())
+                .map(|(&i, &odds)| data.probs[i] * odds as f64)
+                .collect(),
+            None => self.array_indices.iter().map(|&i| data.ers[i]).collect(),
+        }
     }
 
     /// Returns the sum of expected return of the bets
@@ -198,6 +212,32 @@ impl Bets {
         self.expected_return_list(nfc).iter().sum()
     }
 
+    /// Returns the bets that are duplicates, or dominated by one of their sub-bets,
+    /// with suggested replacements
+    pub fn dominated_bets(&self, nfc: &NeoFoodClub) -> Vec<DominatedBet> {
+        find_dominated_bets(nfc, self)
+    }
+
+    /// Returns the smallest win probability that makes each bet +EV at its odds
+    pub fn break_even_probabilities(&self, nfc: &NeoFoodClub) -> Vec<f64> {
+        self.odds_values(nfc)
+            .into_iter()
+            .map(break_even_probability)
+            .collect()
+    }
+
+    /// Returns how far the model's probability of each bet is above its break-even probability,
+    /// negative for bets that are -EV
+    pub fn break_even_margins(&self, nfc: &NeoFoodClub) -> Vec<f64> {
+        let data = nfc.round_dict_data();
+
+        self.array_indices
+            .iter()
+            .zip(self.odds_values(nfc))
+            .map(|(&i, odds)| data.break_even_margin_at(i, odds))
+            .collect()
+    }
+
     /// Fills the bet amounts in-place with the maximum possible amount to hit 1 million.
     /// In short, for each bet we divide 1_000_000 by the odds, and round up.
     /// Then we use whichever is smaller, the bet amount or the result of that equation.
@@ -206,6 +246,13 @@ impl Bets {
             return;
         };
 
+        self.fill_bet_amounts_with(nfc, bet_amount);
+    }
+
+    /// Same as `fill_bet_amounts`, but with the given bet amount instead of the NeoFoodClub's.
+    pub fn fill_bet_amounts_with(&mut self, nfc: &NeoFoodClub, bet_amount: u32) {
+        let bet_amount = bet_amount.clamp(BET_AMOUNT_MIN, BET_AMOUNT_MAX);
+
         let mut amounts = Vec::<Option<u32>>::with_capacity(self.array_indices.len());
         for odds in self.odds_values(nfc).iter() {
             let mut div = 1_000_000 / odds;
@@ -232,6 +279,14 @@ impl Bets {
         Self::new(nfc, bin_indices)
     }
 
+    /// Rebuilds these bets on another version of the round, keeping their order and bet amounts.
+    /// Any locked odds are ignored, since the point is to follow that round's odds.
+    pub(crate) fn rebuild(&self, nfc: &NeoFoodClub, binaries: &[u32]) -> Self {
+        let mut bets = Self::from_binaries(nfc, binaries.to_vec());
+        bets.bet_amounts = self.bet_amounts.clone();
+        bets
+    }
+
     /// Creates a new Bets struct from a hash
     pub fn from_hash(nfc: &NeoFoodClub, hash: &str) -> Result<Self, String> {
         let binaries = bets_hash_to_bet_binaries(hash)?;
@@ -282,6 +337,15 @@ impl Bets {
             .map(|amounts| bet_amounts_to_amounts_hash(amounts))
     }
 
+    /// Returns the payout distribution of the bets in neopoints,
+    /// using each bet's amount and the 1,000,000 NP cap.
+    /// Returns None if there are no bet amounts.
+    pub fn np_odds(&self, nfc: &NeoFoodClub) -> Option<NpOdds> {
+        self.bet_amounts
+            .as_ref()
+            .map(|amounts| NpOdds::new(nfc, &self.array_indices, &self.odds_values(nfc), amounts))
+    }
+
     /// Returns whether or not this set is capable of busting
     /// if there are no odds, returns None
     pub fn is_bustproof(&self) -> bool {
@@ -346,27 +410,91 @@ impl Bets {
             return false;
         }
 
-        let highest_bet_amount = amounts.iter().flatten().max().unwrap();
+        let highest_bet_amount = *amounts.iter().flatten().max().unwrap();
 
         let lowest_winning_bet_amount = self
             .odds_values(nfc)
             .iter()
             .zip(amounts.iter().flatten())
-            .map(|(odds, amount)| odds * amount)
+            .map(|(&odds, &amount)| bet_payout(odds, amount))
             .min()
             .unwrap();
 
-        highest_bet_amount < &lowest_winning_bet_amount
+        highest_bet_amount < lowest_winning_bet_amount
     }
 
-    /// Returns the odds of the bets
+    /// Returns the odds of the bets, or their locked odds if they have them
     pub fn odds_values(&self, nfc: &NeoFoodClub) -> Vec<u32> {
+        if let Some(locked_odds) = &self.locked_odds {
+            return locked_odds.clone();
+        }
+
         self.array_indices
             .iter()
             .map(|i| nfc.round_dict_data().odds[*i])
             .collect()
     }
 
+    /// Sets the odds each bet was placed at, or clears them with None.
+    /// Payouts, ER, NE and the odds distribution will use these from now on.
+    /// Returns an error if the amount of odds doesn't match the bet count,
+    /// or if any odds are 0.
+    pub fn set_locked_odds(
+        &mut self,
+        nfc: &NeoFoodClub,
+        locked_odds: Option<Vec<u32>>,
+    ) -> Result<(), String> {
+        if let Some(locked_odds) = &locked_odds {
+            if locked_odds.len() != self.array_indices.len() {
+                return Err(format!(
+                    "Locked odds must be the same length as bet indices. Provided: {} Expected: {}",
+                    locked_odds.len(),
+                    self.array_indices.len()
+                ));
+            }
+
+            if locked_odds.contains(&0) {
+                return Err("Locked odds must be greater than 0.".to_string());
+            }
+        }
+
+        self.locked_odds = locked_odds;
+        self.odds = Odds::with_odds_values(nfc, &self.array_indices, &self.odds_values(nfc));
+
+        Ok(())
+    }
+
+    /// Returns the odds each bet was placed at, if they were locked.
+    pub fn locked_odds(&self) -> &Option<Vec<u32>> {
+        &self.locked_odds
+    }
+
+    /// Locks each bet to the odds that were showing at the given time,
+    /// resolved through every change of the round, or the opening odds if there are none.
+    /// These are the odds the site actually showed, so the modifier's custom odds and time are ignored.
+    pub fn lock_odds_at(
+        &mut self,
+        nfc: &NeoFoodClub,
+        timestamp: DateTime<Utc>,
+    ) -> Result<(), String> {
+        let odds = nfc.full_odds_timeline().odds_at(timestamp);
+
+        let locked_odds = self
+            .get_indices()
+            .iter()
+            .map(|indices| {
+                indices
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &index)| index > 0)
+                    .map(|(arena, &index)| odds[arena][index as usize] as u32)
+                    .product()
+            })
+            .collect();
+
+        self.set_locked_odds(nfc, Some(locked_odds))
+    }
+
     /// Makes a URL for the bets using the NeoFoodClub object
     pub fn make_url(&self, nfc: &NeoFoodClub, include_domain: bool, all_data: bool) -> String {
         nfc.make_url(Some(self), include_domain, all_data)
@@ -407,17 +535,69 @@ impl Bets {
 
     /// Returns a table visualization of the bets, with stats
     pub fn stats_table(&self, nfc: &NeoFoodClub) -> String {
+        self.make_stats_table(nfc, None).to_string()
+    }
+
+    /// Returns a table visualization of the bets, with stats,
+    /// marking the bets that hit and followed by a summary of the winnings.
+    /// Returns None if there are no winners yet.
+    pub fn results_table(&self, nfc: &NeoFoodClub) -> Option<String> {
+        let results = nfc.bet_results(self)?;
+
+        let mut lines = vec![self.make_stats_table(nfc, Some(&results)).to_string()];
+
+        let hits = results.iter().filter(|r| r.hit).collect::<Vec<_>>();
+        lines.push(format!("Hits: {}/{}", hits.len(), results.len()));
+        lines.push(format!(
+            "Units won: {}",
+            hits.iter().map(|r| r.odds).sum::<u32>()
+        ));
+
+        if self.bet_amounts.is_some() {
+            let staked: u64 = results.iter().map(|r| r.stake as u64).sum();
+            let won: u64 = results.iter().map(|r| r.payout as u64).sum();
+            let lost_to_cap: u64 = results.iter().map(|r| r.lost_to_cap()).sum();
+
+            lines.push(format!("Staked: {staked} NP"));
+            lines.push(format!("Won: {won} NP"));
+            lines.push(format!("Profit: {} NP", won as i64 - staked as i64));
+
+            if lost_to_cap > 0 {
+                lines.push(format!("Lost to cap: {lost_to_cap} NP"));
+            }
+        }
+
+        Some(lines.join("\n"))
+    }
+
+    fn make_stats_table(&self, nfc: &NeoFoodClub, results: Option<&[BetResult]>) -> Table {
         let mut table = Table::new();
 
-        let mut headers = vec!["#", "Odds", "ER"];
+        let mut headers = vec!["#"];
+
+        if results.is_some() {
+            headers.push("Hit");
+        }
+
+        headers.extend(vec!["Odds", "ER", "BE", "Margin"]);
 
         let nes = self.net_expected_list(nfc);
+        let odds_values = self.odds_values(nfc);
+        let ers = self.expected_return_list(nfc);
+        let break_evens = self.break_even_probabilities(nfc);
+        let margins = self.break_even_margins(nfc);
 
         if !nes.is_empty() {
             headers.push("NE");
         }
 
-        headers.extend(vec!["MaxBet", "Hex"]);
+        headers.push("MaxBet");
+
+        if results.is_some() {
+            headers.push("Won");
+        }
+
+        headers.push("Hex");
 
         headers.extend(ARENA_NAMES);
 
@@ -433,28 +613,36 @@ impl Bets {
         {
             let mut row = vec![(bet_index + 1).to_string()];
 
-            let bin_index = nfc
-                .round_dict_data()
-                .bins
-                .iter()
-                .position(|&r| r == *bet_binary)
-                .unwrap();
+            let result = results.map(|results| &results[bet_index]);
+
+            if let Some(result) = result {
+                row.push(if result.hit { "✓" } else { "" }.to_string());
+            }
 
             let hex = format!("0x{bet_binary:0>5X}");
 
             row.extend(vec![
-                nfc.round_dict_data().odds[bin_index].to_string(),
-                format!("{:.3}:1", nfc.round_dict_data().ers[bin_index]),
+                odds_values[bet_index].to_string(),
+                format!("{:.3}:1", ers[bet_index]),
+                format!("{:.3}%", break_evens[bet_index] * 100.0),
+                format!("{:+.3}%", margins[bet_index] * 100.0),
             ]);
 
             if !nes.is_empty() {
                 row.push(format!("{:.2}", nes[bet_index]));
             }
 
-            row.extend(vec![
-                nfc.round_dict_data().maxbets[bin_index].to_string(),
-                hex,
-            ]);
+            row.push(1_000_000u32.div_ceil(odds_values[bet_index]).to_string());
+
+            if let Some(result) = result {
+                row.push(match (result.hit, self.bet_amounts.is_some()) {
+                    (false, _) => "".to_string(),
+                    (true, true) => result.payout.to_string(),
+                    (true, false) => result.odds.to_string(),
+                });
+            }
+
+            row.push(hex);
 
             for (arena_index, pirate_index) in bet_indices.iter().enumerate() {
                 if pirate_index == &0 {
@@ -475,6 +663,6 @@ impl Bets {
             column.set_cell_alignment(comfy_table::CellAlignment::Center);
         }
 
-        table.to_string()
+        table
     }
 }
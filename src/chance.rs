@@ -6,3 +6,15 @@ pub struct Chance {
     pub cumulative: f64,
     pub tail: f64,
 }
+
+/// A struct to represent the likelihood of earning \<payout\> neopoints
+#[derive(Debug, Clone)]
+pub struct NpChance {
+    /// The neopoints paid out, after each bet's 1,000,000 NP cap.
+    pub payout: u32,
+    /// The payout minus the total amount staked.
+    pub profit: i64,
+    pub probability: f64,
+    pub cumulative: f64,
+    pub tail: f64,
+}
@@ -0,0 +1,134 @@
+use crate::{bets::Bets, nfc::NeoFoodClub, outcome::Outcome};
+
+/// What two sets of bets pay out for one possible set of winners.
+#[derive(Debug, Clone)]
+pub struct JointOutcome {
+    /// The winning pirate index of each arena.
+    pub winners: [u8; 5],
+    /// The probability of these winners.
+    pub probability: f64,
+    /// What the first set pays out.
+    pub a: u32,
+    /// What the second set pays out.
+    pub b: u32,
+}
+
+/// A head-to-head comparison of two sets of bets on the same round.
+///
+/// Payouts are in neopoints if both sets have bet amounts, and in units otherwise.
+#[derive(Debug, Clone)]
+pub struct BetsComparison {
+    /// The joint outcome of both sets for every possible set of winners.
+    outcomes: Vec<JointOutcome>,
+
+    /// Whether or not the payouts are in neopoints.
+    in_np: bool,
+
+    /// The bet binaries found in both sets, in the order of the first set.
+    shared_bets: Vec<u32>,
+
+    /// The expected return of each set.
+    expected_returns: (f64, f64),
+}
+
+impl BetsComparison {
+    pub fn new(nfc: &NeoFoodClub, a: &Bets, b: &Bets) -> Self {
+        let in_np = a.bet_amounts.is_some() && b.bet_amounts.is_some();
+
+        let payout = |outcome: &Outcome| match (in_np, outcome.np) {
+            (true, Some(np)) => np,
+            _ => outcome.units,
+        };
+
+        let outcomes = nfc
+            .payoff_table(a)
+            .iter()
+            .zip(nfc.payoff_table(b).iter())
+            .map(|(outcome_a, outcome_b)| JointOutcome {
+                winners: outcome_a.winners,
+                probability: outcome_a.probability,
+                a: payout(outcome_a),
+                b: payout(outcome_b),
+            })
+            .collect();
+
+        let shared_bets = a
+            .bet_binaries
+            .iter()
+            .filter(|bin| b.bet_binaries.contains(bin))
+            .copied()
+            .collect();
+
+        Self {
+            outcomes,
+            in_np,
+            shared_bets,
+            expected_returns: (a.expected_return(nfc), b.expected_return(nfc)),
+        }
+    }
+
+    /// The joint outcome of both sets for every possible set of winners.
+    pub fn outcomes(&self) -> &[JointOutcome] {
+        &self.outcomes
+    }
+
+    /// Whether or not the payouts are in neopoints, rather than units.
+    pub fn in_np(&self) -> bool {
+        self.in_np
+    }
+
+    /// The probability that the first set pays out more than the second.
+    pub fn probability_a_wins(&self) -> f64 {
+        self.probability_where(|o| o.a > o.b)
+    }
+
+    /// The probability that the second set pays out more than the first.
+    pub fn probability_b_wins(&self) -> f64 {
+        self.probability_where(|o| o.b > o.a)
+    }
+
+    /// The probability that both sets pay out the same.
+    pub fn probability_tie(&self) -> f64 {
+        self.probability_where(|o| o.a == o.b)
+    }
+
+    /// The correlation of the two sets' payouts.
+    /// Returns None if either set always pays out the same.
+    pub fn correlation(&self) -> Option<f64> {
+        let mean_a = self.mean(|o| o.a as f64);
+        let mean_b = self.mean(|o| o.b as f64);
+
+        let covariance = self.mean(|o| (o.a as f64 - mean_a) * (o.b as f64 - mean_b));
+        let variance_a = self.mean(|o| (o.a as f64 - mean_a).powi(2));
+        let variance_b = self.mean(|o| (o.b as f64 - mean_b).powi(2));
+
+        if variance_a <= 0.0 || variance_b <= 0.0 {
+            return None;
+        }
+
+        Some(covariance / (variance_a * variance_b).sqrt())
+    }
+
+    /// The bet binaries found in both sets, in the order of the first set.
+    pub fn shared_bets(&self) -> &[u32] {
+        &self.shared_bets
+    }
+
+    /// How far the second set's expected return trails the first's.
+    /// Negative if the second set has the higher expected return.
+    pub fn er_gap(&self) -> f64 {
+        self.expected_returns.0 - self.expected_returns.1
+    }
+
+    fn probability_where(&self, predicate: impl Fn(&JointOutcome) -> bool) -> f64 {
+        self.outcomes
+            .iter()
+            .filter(|o| predicate(o))
+            .map(|o| o.probability)
+            .sum()
+    }
+
+    fn mean(&self, value: impl Fn(&JointOutcome) -> f64) -> f64 {
+        self.outcomes.iter().map(|o| value(o) * o.probability).sum()
+    }
+}
@@ -0,0 +1,85 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::{oddschange::OddsChange, round_data::RoundData};
+
+/// The differences between two snapshots of the same round's data.
+#[derive(Debug, Clone)]
+pub struct RoundDiff {
+    /// The odds that changed, in the same shape as the round's `changes`,
+    /// sorted by arena and then pirate.
+    /// Arenas whose lineups don't match are skipped.
+    pub changes: Vec<OddsChange>,
+
+    /// The winners, if they were resolved between the two snapshots.
+    pub winners: Option<[u8; 5]>,
+
+    /// The indices of the arenas whose foods don't match.
+    /// Foods appearing for the first time are not a mismatch.
+    pub food_mismatches: Vec<usize>,
+
+    /// The indices of the arenas whose pirates don't match.
+    pub lineup_mismatches: Vec<usize>,
+}
+
+impl RoundDiff {
+    /// Compares an older and a newer snapshot of the same round,
+    /// stamping any odds changes with the given time.
+    /// Returns an error if the snapshots are from different rounds.
+    pub fn new(
+        older: &RoundData,
+        newer: &RoundData,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Self, String> {
+        if older.round != newer.round {
+            return Err(format!(
+                "Snapshots must be from the same round. Provided: {} and {}",
+                older.round, newer.round
+            ));
+        }
+
+        let lineup_mismatches: Vec<usize> = (0..5)
+            .filter(|&arena| older.pirates[arena] != newer.pirates[arena])
+            .collect();
+
+        let food_mismatches: Vec<usize> = match (older.foods, newer.foods) {
+            (Some(older_foods), Some(newer_foods)) => (0..5)
+                .filter(|&arena| older_foods[arena] != newer_foods[arena])
+                .collect(),
+            _ => vec![],
+        };
+
+        let t = timestamp.to_rfc3339_opts(SecondsFormat::Secs, false);
+
+        let changes = (0..5)
+            .filter(|arena| !lineup_mismatches.contains(arena))
+            .flat_map(|arena| (1..5).map(move |pirate| (arena, pirate)))
+            .filter_map(|(arena, pirate)| {
+                let old = older.currentOdds[arena][pirate];
+                let new = newer.currentOdds[arena][pirate];
+
+                (old != new)
+                    .then(|| OddsChange::new(t.clone(), new, old, arena as u8, pirate as u8))
+            })
+            .collect();
+
+        let is_resolved = |winners: Option<[u8; 5]>| winners.is_some_and(|w| w != [0; 5]);
+        let winners = (!is_resolved(older.winners) && is_resolved(newer.winners))
+            .then_some(newer.winners)
+            .flatten();
+
+        Ok(Self {
+            changes,
+            winners,
+            food_mismatches,
+            lineup_mismatches,
+        })
+    }
+
+    /// Whether or not nothing differs between the two snapshots.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+            && self.winners.is_none()
+            && self.food_mismatches.is_empty()
+            && self.lineup_mismatches.is_empty()
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    bets::Bets,
+    math::{bet_payout, RoundDictData, BET_AMOUNT_MIN},
+    nfc::NeoFoodClub,
+};
+
+/// A bet in a set that another bet is at least as good as, in every outcome.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DominatedBet {
+    /// The index of the bet in the set.
+    pub bet_index: usize,
+
+    /// The index of an earlier, identical bet in the set, if this is a duplicate.
+    pub duplicate_of: Option<usize>,
+
+    /// The `RoundDictData` index of the best sub-bet that wins whenever this bet does,
+    /// pays at least as much and has a higher net expected value, if any.
+    pub dominated_by: Option<usize>,
+
+    /// The `RoundDictData` index of the bet suggested in its place, if there's one
+    /// that isn't dominated and isn't already in the set.
+    pub replacement: Option<usize>,
+}
+
+/// For each bet in the round, finds the sub-bet that dominates it at the given bet amount, if any.
+///
+/// Every bet is staked at the bet amount, capped to what it takes to hit 1 million,
+/// like max TER bets are. A sub-bet wins whenever the bet does, so it dominates the bet
+/// if it pays at least as much at its own stake and has a higher net expected value.
+/// When several sub-bets do, the most likely one is picked.
+pub fn find_dominating_sub_bets(data: &RoundDictData, bet_amount: u32) -> Vec<Option<usize>> {
+    let bin_indices: HashMap<u32, usize> = data
+        .bins
+        .iter()
+        .enumerate()
+        .map(|(index, &bin)| (bin, index))
+        .collect();
+
+    let stakes: Vec<u32> = data
+        .maxbets
+        .iter()
+        .map(|&maxbet| maxbet.max(BET_AMOUNT_MIN).min(bet_amount))
+        .collect();
+    let payouts: Vec<u32> = data
+        .odds
+        .iter()
+        .zip(stakes.iter())
+        .map(|(&odds, &stake)| bet_payout(odds, stake))
+        .collect();
+    let net_expected: Vec<f64> = stakes
+        .iter()
+        .zip(data.ers.iter())
+        .map(|(&stake, er)| stake as f64 * er - stake as f64)
+        .collect();
+
+    data.bins
+        .iter()
+        .enumerate()
+        .map(|(index, &bin)| {
+            // walk every non-empty proper subset of the bet's pirates
+            let mut best: Option<usize> = None;
+            let mut sub_bin = (bin - 1) & bin;
+            while sub_bin != 0 {
+                let sub_index = bin_indices[&sub_bin];
+
+                if payouts[sub_index] >= payouts[index]
+                    && net_expected[sub_index] > net_expected[index]
+                    && best.is_none_or(|best| data.probs[sub_index] > data.probs[best])
+                {
+                    best = Some(sub_index);
+                }
+
+                sub_bin = (sub_bin - 1) & bin;
+            }
+
+            best
+        })
+        .collect()
+}
+
+/// Flags the bets in the set that are duplicates, or dominated by one of their sub-bets,
+/// at the round's odds. Bets are compared at their bet amount, or the round's bet amount
+/// if they have none, each capped to what it takes to hit 1 million.
+/// Without either, only duplicates are flagged.
+/// Replacements are the best dominating sub-bet that isn't already in the set,
+/// or else the highest max TER bet that isn't dominated or already in the set.
+pub fn find_dominated_bets(nfc: &NeoFoodClub, bets: &Bets) -> Vec<DominatedBet> {
+    let data = nfc.round_dict_data();
+
+    let stakes: Vec<Option<u32>> = match &bets.bet_amounts {
+        Some(amounts) => amounts
+            .iter()
+            .map(|amount| amount.or(nfc.bet_amount))
+            .collect(),
+        None => vec![nfc.bet_amount; bets.len()],
+    };
+
+    let mut dominating_by_stake: HashMap<u32, Vec<Option<usize>>> = HashMap::new();
+    let mut first_seen: HashMap<usize, usize> = HashMap::new();
+    let mut taken: HashSet<usize> = bets.array_indices.iter().copied().collect();
+    let max_ter_indices = nfc.max_ter_indices();
+
+    let mut dominated = vec![];
+
+    for (bet_index, (&array_index, stake)) in
+        bets.array_indices.iter().zip(stakes.iter()).enumerate()
+    {
+        let duplicate_of = first_seen.get(&array_index).copied();
+        first_seen.entry(array_index).or_insert(bet_index);
+
+        let dominating = stake.map(|stake| {
+            dominating_by_stake
+                .entry(stake)
+                .or_insert_with(|| find_dominating_sub_bets(data, stake))
+        });
+
+        let dominated_by = dominating
+            .as_ref()
+            .and_then(|dominating| dominating[array_index]);
+
+        if duplicate_of.is_none() && dominated_by.is_none() {
+            continue;
+        }
+
+        let replacement = dominated_by
+            .filter(|index| !taken.contains(index))
+            .or_else(|| {
+                max_ter_indices.iter().copied().find(|index| {
+                    !taken.contains(index)
+                        && dominating
+                            .as_ref()
+                            .is_none_or(|dominating| dominating[*index].is_none())
+                })
+            });
+
+        if let Some(replacement) = replacement {
+            taken.insert(replacement);
+        }
+
+        dominated.push(DominatedBet {
+            bet_index,
+            duplicate_of,
+            dominated_by,
+            replacement,
+        });
+    }
+
+    dominated
+}
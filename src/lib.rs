@@ -1,6 +1,10 @@
 pub mod arena;
+pub mod bankroll;
 pub mod bets;
 pub mod chance;
+pub mod compare;
+pub mod diff;
+pub mod dominance;
 pub mod food_adjustments;
 pub mod math;
 pub mod models;
@@ -8,6 +12,12 @@ pub mod modifier;
 pub mod nfc;
 pub mod odds;
 pub mod oddschange;
+pub mod outcome;
 pub mod pirates;
 pub mod round_data;
+pub mod sensitivity;
+pub mod simulation;
+pub mod timeline;
+pub mod timing;
 pub mod utils;
+pub mod volatility;
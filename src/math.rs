@@ -345,6 +345,58 @@ pub fn bets_indices_to_bet_binaries(bets_indices: Vec<[u8; 5]>) -> Vec<u32> {
         .collect()
 }
 
+/// Returns every possible set of winners, 4^5 = 1024 in total.
+/// ```
+/// let winners = neofoodclub::math::winner_combinations();
+/// assert_eq!(winners.len(), 1024);
+/// assert_eq!(winners[0], [1, 1, 1, 1, 1]);
+/// assert_eq!(winners[1023], [4, 4, 4, 4, 4]);
+/// ```
+pub fn winner_combinations() -> Vec<[u8; 5]> {
+    let mut combinations = Vec::with_capacity(1024);
+    for a in 1..=4 {
+        for b in 1..=4 {
+            for c in 1..=4 {
+                for d in 1..=4 {
+                    for e in 1..=4 {
+                        combinations.push([a, b, c, d, e]);
+                    }
+                }
+            }
+        }
+    }
+    combinations
+}
+
+/// Returns the probability of the given set of winners happening.
+#[inline]
+pub fn winners_probability(winners: [u8; 5], probabilities: [[f64; 5]; 5]) -> f64 {
+    winners
+        .iter()
+        .enumerate()
+        .map(|(arena, &index)| probabilities[arena][index as usize])
+        .product()
+}
+
+/// Returns the neopoints a winning bet pays out, capped at 1,000,000.
+/// ```
+/// let payout = neofoodclub::math::bet_payout(12, 8000);
+/// assert_eq!(payout, 96_000);
+///
+/// let payout = neofoodclub::math::bet_payout(371_293, 70_304);
+/// assert_eq!(payout, 1_000_000);
+/// ```
+#[inline]
+pub fn bet_payout(odds: u32, amount: u32) -> u32 {
+    (odds as u64 * amount as u64).min(1_000_000) as u32
+}
+
+/// Returns the smallest win probability that makes a bet at the given odds +EV.
+#[inline]
+pub fn break_even_probability(odds: u32) -> f64 {
+    1.0 / odds as f64
+}
+
 #[inline]
 fn ib_doable(binary: u32) -> bool {
     (binary & 0xF0000 != 0)
@@ -428,6 +480,24 @@ pub struct RoundDictData {
     pub maxbets: Vec<u32>,
 }
 
+impl RoundDictData {
+    /// Returns the smallest win probability that makes the bet at the given index +EV at its odds.
+    pub fn break_even_probability(&self, index: usize) -> f64 {
+        break_even_probability(self.odds[index])
+    }
+
+    /// Returns how far the model's probability of the bet at the given index is
+    /// above its break-even probability. Negative if the bet is -EV.
+    pub fn break_even_margin(&self, index: usize) -> f64 {
+        self.break_even_margin_at(index, self.odds[index])
+    }
+
+    /// Like `break_even_margin`, but with the bet placed at the given odds instead.
+    pub fn break_even_margin_at(&self, index: usize, odds: u32) -> f64 {
+        self.probs[index] - break_even_probability(odds)
+    }
+}
+
 pub fn make_round_dicts(stds: [[f64; 5]; 5], odds: [[u8; 5]; 5]) -> RoundDictData {
     let mut bins: Vec<u32> = Vec::with_capacity(3124);
     let mut probs: Vec<f64> = Vec::with_capacity(3124);
@@ -484,6 +554,28 @@ pub fn make_round_dicts(stds: [[f64; 5]; 5], odds: [[u8; 5]; 5]) -> RoundDictDat
     }
 }
 
+/// Recomputes the odds, ERs and max bets of the rows whose bets touch the arenas in `arenas_mask`,
+/// a union of `BIT_MASKS`. Probabilities are left as they are, since they don't depend on current odds.
+pub fn update_round_dict_rows(data: &mut RoundDictData, odds: [[u8; 5]; 5], arenas_mask: u32) {
+    for row in 0..data.bins.len() {
+        let bin = data.bins[row];
+        if bin & arenas_mask == 0 {
+            continue;
+        }
+
+        let total_odds: u32 = binary_to_indices(bin)
+            .iter()
+            .enumerate()
+            .filter(|(_, &index)| index > 0)
+            .map(|(arena, &index)| odds[arena][index as usize] as u32)
+            .product();
+
+        data.odds[row] = total_odds;
+        data.ers[row] = data.probs[row] * total_odds as f64;
+        data.maxbets[row] = (1_000_000.0 / total_odds as f64).ceil() as u32;
+    }
+}
+
 pub fn build_chance_objects(
     bets: &[[u8; 5]],
     bet_odds: &[u32],
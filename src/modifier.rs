@@ -1,10 +1,17 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 
 use bitflags::bitflags;
-use chrono::NaiveTime;
-use chrono_tz::US::Pacific;
+use chrono::{DateTime, NaiveTime, SecondsFormat, TimeDelta, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
 
-use crate::round_data::RoundData;
+use crate::{
+    round_data::RoundData,
+    timeline::OddsTimeline,
+    utils::{close_from_start, convert_from_utc_to_nst, nst_to_utc},
+};
 
 bitflags! {
     /// A set of flags for modifiers.
@@ -28,18 +35,139 @@ bitflags! {
     }
 }
 
+/// A point in time during a round, for `Modifier.custom_time`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomTime {
+    /// A time of day in NST, on the first day of the round at or after its start.
+    Nst(NaiveTime),
+    /// An exact instant.
+    At(DateTime<Utc>),
+    /// An amount of time after the round starts.
+    AfterStart(TimeDelta),
+    /// An amount of time before the round closes.
+    BeforeClose(TimeDelta),
+}
+
+impl CustomTime {
+    /// Resolves this to an exact instant, for a round starting at the given time.
+    /// NST times of day that happen twice when clocks fall back resolve to the earlier one,
+    /// and ones skipped when clocks spring forward are shifted forward by an hour.
+    pub fn resolve(&self, start: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            CustomTime::Nst(time) => {
+                let start_nst = convert_from_utc_to_nst(start);
+
+                let mut date = start_nst.date_naive();
+                // if the custom time is before the start time, it's on the next day
+                if *time < start_nst.time() {
+                    date = date.succ_opt().unwrap();
+                }
+
+                nst_to_utc(date.and_time(*time))
+            }
+            CustomTime::At(instant) => *instant,
+            CustomTime::AfterStart(offset) => start + *offset,
+            CustomTime::BeforeClose(offset) => close_from_start(start) - *offset,
+        }
+    }
+}
+
+impl fmt::Display for CustomTime {
+    /// Formats the custom time the way it's written in URLs:
+    /// `HH:MM:SS` for NST times of day, RFC 3339 for instants,
+    /// and `start+N` or `close-N` for offsets, in seconds.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomTime::Nst(time) => write!(f, "{}", time.format("%H:%M:%S")),
+            CustomTime::At(instant) => {
+                write!(f, "{}", instant.to_rfc3339_opts(SecondsFormat::Secs, false))
+            }
+            CustomTime::AfterStart(offset) => write!(f, "start+{}", offset.num_seconds()),
+            CustomTime::BeforeClose(offset) => write!(f, "close-{}", offset.num_seconds()),
+        }
+    }
+}
+
+impl FromStr for CustomTime {
+    type Err = String;
+
+    /// Parses a custom time in any of the formats written by `Display`.
+    /// Spaces are read as `+`, since that's what an unescaped `+` decodes to in a query string.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = &s.replace(' ', "+");
+
+        let seconds = |offset: &str| {
+            offset
+                .parse::<i64>()
+                .ok()
+                .and_then(TimeDelta::try_seconds)
+                .ok_or(format!("Invalid custom time offset: {s}"))
+        };
+
+        if let Some(offset) = s.strip_prefix("start+") {
+            return Ok(CustomTime::AfterStart(seconds(offset)?));
+        }
+
+        if let Some(offset) = s.strip_prefix("close-") {
+            return Ok(CustomTime::BeforeClose(seconds(offset)?));
+        }
+
+        if let Ok(instant) = DateTime::parse_from_rfc3339(s) {
+            return Ok(CustomTime::At(instant.with_timezone(&Utc)));
+        }
+
+        NaiveTime::parse_from_str(s, "%H:%M:%S")
+            .map(CustomTime::Nst)
+            .map_err(|_| format!("Invalid custom time: {s}"))
+    }
+}
+
+impl From<NaiveTime> for CustomTime {
+    fn from(time: NaiveTime) -> Self {
+        CustomTime::Nst(time)
+    }
+}
+
+impl From<DateTime<Utc>> for CustomTime {
+    fn from(instant: DateTime<Utc>) -> Self {
+        CustomTime::At(instant)
+    }
+}
+
+impl From<DateTime<Tz>> for CustomTime {
+    fn from(instant: DateTime<Tz>) -> Self {
+        CustomTime::At(instant.with_timezone(&Utc))
+    }
+}
+
+/// An override for a pirate's win probability, for `Modifier.custom_probabilities`.
+/// In URLs, it's written as `{"pin":0.5}` or `{"scale":1.2}`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbabilityOverride {
+    /// Sets the probability to this value, from 0 to 1.
+    Pin(f64),
+    /// Multiplies the model's probability by this factor.
+    Scale(f64),
+}
+
 /// A struct to represent a modifier.
 ///
 /// A modifier is a set of flags that affect the way certain bets are calculated,
 /// as well as custom odds and custom time.
 /// Custom odds is a map of pirate IDs to odds.
-/// Custom time is in NST. When set, this will change the current odds to the opening odds,
+/// Custom time is a point during the round. When set, this will change the current odds to the opening odds,
 /// and then apply the odds changes up to the custom time, as if making the bets at that time.
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Custom probabilities is a map of pirate IDs to probability overrides, applied after the model runs.
+/// The other pirates in an overridden pirate's arena are renormalized so the arena still adds up to 1.
+/// Custom foods is a map of arena indices to the foods to use for those arenas instead of the round's.
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Modifier {
     pub value: i32,
     pub custom_odds: Option<HashMap<u8, u8>>,
-    pub custom_time: Option<NaiveTime>,
+    pub custom_time: Option<CustomTime>,
+    pub custom_probabilities: Option<HashMap<u8, ProbabilityOverride>>,
+    pub custom_foods: Option<HashMap<u8, [u8; 10]>>,
 }
 
 impl Modifier {
@@ -63,9 +191,73 @@ impl Modifier {
         Ok(Self {
             value,
             custom_odds,
-            custom_time,
+            custom_time: custom_time.map(CustomTime::Nst),
+            custom_probabilities: None,
+            custom_foods: None,
         })
     }
+
+    /// Sets the custom time, for times other than an NST time of day.
+    pub fn with_custom_time(mut self, custom_time: Option<CustomTime>) -> Self {
+        self.custom_time = custom_time;
+        self
+    }
+
+    /// Sets the custom probabilities.
+    /// Returns an error if a pirate ID isn't 1-20, a pinned probability isn't 0-1,
+    /// or a scale is negative.
+    pub fn with_custom_probabilities(
+        mut self,
+        custom_probabilities: Option<HashMap<u8, ProbabilityOverride>>,
+    ) -> Result<Self, String> {
+        if let Some(custom_probabilities) = custom_probabilities.as_ref() {
+            for (key, value) in custom_probabilities.iter() {
+                if *key < 1 || *key > 20 {
+                    return Err(format!("Invalid pirate ID, need 1-20, got {}", *key));
+                }
+                match *value {
+                    ProbabilityOverride::Pin(probability) => {
+                        if !(0.0..=1.0).contains(&probability) {
+                            return Err(format!(
+                                "Invalid probability, need 0-1, got {probability}"
+                            ));
+                        }
+                    }
+                    ProbabilityOverride::Scale(scale) => {
+                        if !scale.is_finite() || scale < 0.0 {
+                            return Err(format!(
+                                "Invalid probability scale, need 0 or more, got {scale}"
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        self.custom_probabilities = custom_probabilities;
+        Ok(self)
+    }
+
+    /// Sets the custom foods.
+    /// Returns an error if an arena index isn't 0-4, or a food ID isn't 1-40.
+    pub fn with_custom_foods(
+        mut self,
+        custom_foods: Option<HashMap<u8, [u8; 10]>>,
+    ) -> Result<Self, String> {
+        if let Some(custom_foods) = custom_foods.as_ref() {
+            for (key, foods) in custom_foods.iter() {
+                if *key > 4 {
+                    return Err(format!("Invalid arena index, need 0-4, got {}", *key));
+                }
+                if let Some(food) = foods.iter().find(|&&food| !(1..=40).contains(&food)) {
+                    return Err(format!("Invalid food ID, need 1-40, got {food}"));
+                }
+            }
+        }
+
+        self.custom_foods = custom_foods;
+        Ok(self)
+    }
 }
 
 impl Modifier {
@@ -110,40 +302,37 @@ impl Modifier {
 
         // apply custom time if necessary
         // only can if start is Some, and custom_time is Some, and changes is Some
-        if let Some(start_time_as_nst) = &round_data.start_nst() {
+        if let Some(start) = round_data.start_utc() {
             if let Some(custom_time) = &self.custom_time {
-                if let Some(changes) = &round_data.changes {
-                    let mut temp_odds = round_data.openingOdds; // as a starting point
-
-                    let mut custom_time = start_time_as_nst
-                        .date_naive()
-                        .and_time(*custom_time)
-                        .and_local_timezone(Pacific)
-                        .unwrap();
-
-                    // if the custom time is before the start time, we need to add a day
-                    if custom_time < *start_time_as_nst {
-                        custom_time += chrono::Duration::try_days(1).unwrap();
-                    }
+                if round_data.changes.is_some() {
+                    let timeline = OddsTimeline::new(round_data);
+                    let custom_time = custom_time.resolve(start);
 
-                    let new_changes = changes
-                        .iter()
-                        .filter(|change| change.timestamp_nst() <= custom_time)
+                    let new_changes = timeline
+                        .changes_until(custom_time)
                         .cloned()
                         .collect::<Vec<_>>();
 
-                    if !new_changes.is_empty() {
-                        for change in new_changes.iter() {
-                            temp_odds[change.arena_index()][change.pirate_index()] = change.new;
-                        }
+                    round_data.changes = (!new_changes.is_empty()).then_some(new_changes);
+                    round_data.customOdds = Some(timeline.odds_at(custom_time));
+                }
+            }
+        }
 
-                        round_data.changes = Some(new_changes);
-                    } else {
-                        round_data.changes = None;
-                    }
+        // apply custom foods if necessary
+        // rounds without foods can only use them if every arena has custom foods
+        if let Some(custom_foods) = &self.custom_foods {
+            let base_foods = round_data
+                .customFoods
+                .or(round_data.foods)
+                .or_else(|| (custom_foods.len() == 5).then_some([[0; 10]; 5]));
 
-                    round_data.customOdds = Some(temp_odds);
+            if let Some(mut temp_foods) = base_foods {
+                for (arena_index, foods) in custom_foods.iter() {
+                    temp_foods[*arena_index as usize] = *foods;
                 }
+
+                round_data.customFoods = Some(temp_foods);
             }
         }
 
@@ -166,12 +355,76 @@ impl Modifier {
         }
     }
 
+    /// Applies the custom probabilities to the probabilities a model came up with for the round.
+    /// Pinned pirates get their pinned probability, scaled pirates are scaled,
+    /// and the rest of the arena is renormalized around the pinned ones.
+    /// Arenas whose pins add up to more than 1, or that would have no probability left at all,
+    /// keep the model's probabilities.
+    pub fn apply_probabilities(
+        &self,
+        round_data: &RoundData,
+        mut probabilities: [[f64; 5]; 5],
+    ) -> [[f64; 5]; 5] {
+        let Some(custom_probabilities) = &self.custom_probabilities else {
+            return probabilities;
+        };
+
+        for (arena_index, arena) in round_data.pirates.iter().enumerate() {
+            let overrides = arena.map(|pirate| custom_probabilities.get(&pirate));
+            if overrides.iter().all(Option::is_none) {
+                continue;
+            }
+
+            let mut probs = probabilities[arena_index];
+            let mut pinned_total = 0.0;
+            let mut free_total = 0.0;
+
+            for (pirate_index, pirate_override) in overrides.iter().enumerate() {
+                let prob = &mut probs[pirate_index + 1];
+                match pirate_override {
+                    Some(ProbabilityOverride::Pin(pinned)) => {
+                        *prob = *pinned;
+                        pinned_total += *pinned;
+                    }
+                    Some(ProbabilityOverride::Scale(scale)) => {
+                        *prob *= *scale;
+                        free_total += *prob;
+                    }
+                    None => free_total += *prob,
+                }
+            }
+
+            // the unpinned pirates share whatever the pinned ones left over
+            if free_total > 0.0 {
+                let free_scale = (1.0 - pinned_total).max(0.0) / free_total;
+                for (pirate_index, pirate_override) in overrides.iter().enumerate() {
+                    if !matches!(pirate_override, Some(ProbabilityOverride::Pin(_))) {
+                        probs[pirate_index + 1] *= free_scale;
+                    }
+                }
+            }
+
+            // in case the pinned ones alone don't add up to 1
+            let total: f64 = probs[1..].iter().sum();
+            if pinned_total > 1.0 + 1e-9 || total <= 0.0 {
+                continue;
+            }
+            probs[1..].iter_mut().for_each(|prob| *prob /= total);
+
+            probabilities[arena_index] = probs;
+        }
+
+        probabilities
+    }
+
     /// Returns a deep copy of the modifier.
     pub fn copy(&self) -> Self {
         Self {
             value: self.value,
             custom_odds: self.custom_odds.clone(),
             custom_time: self.custom_time,
+            custom_probabilities: self.custom_probabilities.clone(),
+            custom_foods: self.custom_foods.clone(),
         }
     }
 }
@@ -1,20 +1,30 @@
 use std::cell::OnceCell;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 use crate::arena::Arenas;
-use crate::bets::Bets;
+use crate::bets::{BetAmounts, Bets};
+use crate::compare::BetsComparison;
+use crate::diff::RoundDiff;
+use crate::dominance::find_dominating_sub_bets;
 use crate::math::{
-    make_round_dicts, pirates_binary, random_full_pirates_binary, RoundDictData, BET_AMOUNT_MAX,
-    BET_AMOUNT_MIN, BIT_MASKS,
+    make_round_dicts, pirates_binary, random_full_pirates_binary, update_round_dict_rows,
+    winner_combinations, RoundDictData, BET_AMOUNT_MAX, BET_AMOUNT_MIN, BIT_MASKS,
 };
-use crate::modifier::{Modifier, ModifierFlags};
+use crate::modifier::{Modifier, ModifierFlags, ProbabilityOverride};
 use crate::oddschange::OddsChange;
+use crate::outcome::{BetResult, Outcome};
 use crate::round_data::RoundData;
-use crate::utils::{argsort_slice_3124, get_dst_offset};
+use crate::sensitivity::OddsSensitivity;
+use crate::simulation::Simulation;
+use crate::timeline::{OddsSnapshot, OddsTimeline};
+use crate::timing::{BetTiming, BetsHistory};
+use crate::utils::{argsort_slice_3124, close_from_start};
+use crate::volatility::OddsVolatility;
 use chrono::{DateTime, Utc};
 use chrono_tz::Tz;
 use itertools::Itertools;
 use rand::seq::IteratorRandom;
+use rand::{Rng, RngExt};
 use serde::{Deserialize, Serialize};
 
 use crate::models::multinomial_logit::MultinomialLogitModel;
@@ -29,9 +39,34 @@ struct UrlAllDataParams<'a> {
     #[serde(rename = "currentOdds")]
     current_odds: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
+    foods: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     winners: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
     timestamp: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<&'a str>,
+    #[serde(rename = "lastChange", skip_serializing_if = "Option::is_none")]
+    last_change: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changes: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct UrlModifierParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modifier: Option<i32>,
+    #[serde(rename = "customOdds", skip_serializing_if = "Option::is_none")]
+    custom_odds: Option<String>,
+    #[serde(rename = "customTime", skip_serializing_if = "Option::is_none")]
+    custom_time: Option<String>,
+    #[serde(
+        rename = "customProbabilities",
+        skip_serializing_if = "Option::is_none"
+    )]
+    custom_probabilities: Option<String>,
+    #[serde(rename = "customFoods", skip_serializing_if = "Option::is_none")]
+    custom_foods: Option<String>,
 }
 
 #[allow(non_snake_case)]
@@ -47,6 +82,29 @@ pub struct RoundDataRaw {
     winners: Option<String>,
     timestamp: Option<String>,
     lastChange: Option<String>,
+    changes: Option<String>,
+    modifier: Option<i32>,
+    customOdds: Option<String>,
+    customTime: Option<String>,
+    customProbabilities: Option<String>,
+    customFoods: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UrlBetsRaw {
+    round: Option<u16>,
+    b: Option<String>,
+    a: Option<String>,
+}
+
+/// Bets read from a NeoFoodClub-like URL.
+#[derive(Debug, Clone)]
+pub struct UrlBets {
+    pub bets: Bets,
+
+    /// Anything odd about the bets that didn't stop them from being read,
+    /// such as there being more bets than can be placed.
+    pub warnings: Vec<String>,
 }
 
 /// The probability model to use when calculating bets.
@@ -57,6 +115,21 @@ pub enum ProbabilityModel {
     MultinomialLogitModel,
 }
 
+/// What to weigh full-arena bets by when sampling them at random.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BetWeighting {
+    /// The model's probability of the bet winning.
+    #[default]
+    Probability,
+    /// The expected return of the bet.
+    ExpectedReturn,
+    /// The net expected value of the bet, given the bet amount.
+    /// Unlike the others, this can be negative, so bets are weighed by the exponent of
+    /// their NE per neopoint of bet amount, rather than in proportion to it.
+    /// Behaves like `ExpectedReturn` if there's no bet amount.
+    NetExpected,
+}
+
 /// A struct to represent the NeoFoodClub object.
 /// This object contains all the data needed to calculate bets,
 /// and methods to create URLs.
@@ -66,6 +139,9 @@ pub struct NeoFoodClub {
     pub bet_amount: Option<u32>,
     pub modifier: Modifier,
     pub probability_model: ProbabilityModel,
+    pub prune_dominated: bool,
+    /// Every change of the round, since a custom time cuts `round_data.changes` short.
+    all_changes: Option<Vec<OddsChange>>,
     arenas: OnceCell<Arenas>,
     stds: OnceCell<[[f64; 5]; 5]>,
     data: OnceCell<RoundDictData>,
@@ -86,6 +162,7 @@ impl NeoFoodClub {
 
         let use_modifier = modifier.unwrap_or_default();
 
+        let all_changes = round_data.changes.clone();
         use_modifier.apply(&mut round_data);
 
         let mut nfc = NeoFoodClub {
@@ -93,6 +170,8 @@ impl NeoFoodClub {
             bet_amount: None,
             modifier: use_modifier,
             probability_model: model.unwrap_or_default(),
+            prune_dominated: false,
+            all_changes,
             arenas: OnceCell::new(),
             stds: OnceCell::new(),
             data: OnceCell::new(),
@@ -110,6 +189,16 @@ impl NeoFoodClub {
     pub fn set_bet_amount(&mut self, amount: Option<u32>) {
         self.bet_amount = amount.map(|x| x.clamp(BET_AMOUNT_MIN, BET_AMOUNT_MAX));
         self.clamped_max_bets = OnceCell::new();
+        self.net_expected_indices = OnceCell::new();
+    }
+
+    /// Sets whether or not bets dominated by one of their sub-bets at the bet amount
+    /// are left out of `make_max_ter_bets` and `make_all_max_ter_bets`.
+    /// The other generators, like tenbets, keep ranking every bet.
+    /// Has no effect without a bet amount.
+    pub fn set_prune_dominated(&mut self, prune: bool) {
+        self.prune_dominated = prune;
+        self.max_ter_indices = OnceCell::new();
     }
 
     /// Lazy loads the Arenas object.
@@ -119,11 +208,16 @@ impl NeoFoodClub {
 
     /// Lazy loads the probabilities.
     pub fn probabilities(&self) -> [[f64; 5]; 5] {
-        *self.stds.get_or_init(|| match self.probability_model {
-            ProbabilityModel::OriginalModel => OriginalModel::new(&self.round_data),
-            ProbabilityModel::MultinomialLogitModel => {
-                MultinomialLogitModel::new(self.get_arenas())
-            }
+        *self.stds.get_or_init(|| {
+            let probabilities = match self.probability_model {
+                ProbabilityModel::OriginalModel => OriginalModel::new(&self.round_data),
+                ProbabilityModel::MultinomialLogitModel => {
+                    MultinomialLogitModel::new(self.get_arenas())
+                }
+            };
+
+            self.modifier
+                .apply_probabilities(&self.round_data, probabilities)
         })
     }
 
@@ -142,6 +236,92 @@ impl NeoFoodClub {
         self.max_ter_indices = OnceCell::new();
         self.net_expected_indices = OnceCell::new();
         self.round_data.customOdds = None;
+        self.round_data.customFoods = None;
+    }
+
+    /// Applies a newer snapshot of this round in place, merging its changes and recomputing only what moved.
+    /// Returns an error if the snapshot is invalid, from another round, or has different pirates.
+    pub fn apply_snapshot(&mut self, snapshot: &RoundData) -> Result<RoundDiff, String> {
+        let timestamp = snapshot
+            .last_change_utc()
+            .or(snapshot.timestamp_utc())
+            .unwrap_or_else(Utc::now);
+
+        let diff = self.round_data.diff(snapshot, timestamp)?;
+
+        if !diff.lineup_mismatches.is_empty() {
+            return Err("Snapshots with different pirates can't be applied.".to_string());
+        }
+
+        check_round_data(snapshot)?;
+
+        let previous_odds = self.custom_odds();
+        let foods_changed = snapshot.foods.is_some() && snapshot.foods != self.round_data.foods;
+
+        let all_changes = self.all_changes.get_or_insert_with(Vec::new);
+        let previous_count = all_changes.len();
+        merge_changes(all_changes, snapshot.changes.as_deref().unwrap_or_default());
+
+        // without changes that account for the new odds, only the net moves can be added,
+        // all stamped with the snapshot's time
+        let logged_odds = OddsTimeline::new(&RoundData {
+            changes: Some(all_changes.clone()),
+            ..self.round_data.clone()
+        })
+        .odds_at(DateTime::<Utc>::MAX_UTC);
+        if logged_odds != snapshot.currentOdds {
+            merge_changes(all_changes, &diff.changes);
+        }
+
+        if all_changes.len() > previous_count {
+            self.round_data.lastChange = snapshot
+                .lastChange
+                .clone()
+                .or_else(|| all_changes.last().map(|change| change.t.clone()));
+        }
+        if all_changes.is_empty() {
+            self.all_changes = None;
+        }
+        self.round_data.changes = self.all_changes.clone();
+        self.round_data.currentOdds = snapshot.currentOdds;
+        self.round_data.timestamp = snapshot
+            .timestamp
+            .clone()
+            .or(self.round_data.timestamp.take());
+        if diff.winners.is_some() {
+            self.round_data.winners = diff.winners;
+        }
+        if foods_changed {
+            self.round_data.foods = snapshot.foods;
+        }
+
+        self.round_data.customOdds = None;
+        self.round_data.customFoods = None;
+        self.modifier.clone().apply(&mut self.round_data);
+
+        let current_odds = self.custom_odds();
+        let arenas_mask = (0..5)
+            .filter(|&arena| previous_odds[arena] != current_odds[arena])
+            .fold(0, |mask, arena| mask | BIT_MASKS[arena]);
+
+        // arenas hold the odds, winners and foods, and are cheap to rebuild
+        self.arenas = OnceCell::new();
+
+        if foods_changed {
+            // foods can change the probabilities, which every row depends on
+            self.clear_caches();
+            self.round_data.changes = self.all_changes.clone();
+            self.modifier.clone().apply(&mut self.round_data);
+        } else if arenas_mask != 0 {
+            if let Some(data) = self.data.get_mut() {
+                update_round_dict_rows(data, current_odds, arenas_mask);
+            }
+            self.clamped_max_bets = OnceCell::new();
+            self.max_ter_indices = OnceCell::new();
+            self.net_expected_indices = OnceCell::new();
+        }
+
+        Ok(diff)
     }
 
     /// changes the modifier of this NeoFoodClub object
@@ -152,12 +332,16 @@ impl NeoFoodClub {
         if self.modified()
             || (current_modifier.custom_odds != modifier.custom_odds
                 || current_modifier.custom_time != modifier.custom_time
+                || current_modifier.custom_probabilities != modifier.custom_probabilities
+                || current_modifier.custom_foods != modifier.custom_foods
                 || current_modifier.is_opening_odds() != modifier.is_opening_odds())
         {
             self.clear_caches();
         }
 
         self.round_data.customOdds = None;
+        self.round_data.customFoods = None;
+        self.round_data.changes = self.all_changes.clone();
 
         self.modifier = modifier;
         self.modifier.apply(&mut self.round_data);
@@ -178,6 +362,8 @@ impl NeoFoodClub {
     }
 
     /// Creates a NeoFoodClub object from a NeoFoodClub-like URL.
+    /// If a modifier is given, its flags are used instead of the URL's `modifier` flags,
+    /// but a `/15/` path still turns on Charity Corner.
     pub fn from_url(
         url: &str,
         bet_amount: Option<u32>,
@@ -190,22 +376,48 @@ impl NeoFoodClub {
             panic!("No relevant NeoFoodClub-like URL data found.");
         }
 
-        let use_modifier = modifier.unwrap_or_default();
-        let cc_perk = parts[0].ends_with("/15/") || use_modifier.is_charity_corner();
-        let new_modifier = Modifier::new(
-            use_modifier.value
-                | if cc_perk {
-                    ModifierFlags::CHARITY_CORNER.bits()
-                } else {
-                    0
-                },
-            use_modifier.custom_odds,
-            use_modifier.custom_time,
-        )
-        .expect("Invalid modifier parameters");
-
         let temp: RoundDataRaw = serde_qs::from_str(parts[1]).expect("Invalid query string.");
 
+        // the given modifier takes precedence over the one in the URL,
+        // its flags replacing the URL's, and its custom odds and time replacing the URL's if set.
+        // the /15/ path is the charity corner perk itself, so it applies either way
+        let charity_corner = if parts[0].ends_with("/15/") {
+            ModifierFlags::CHARITY_CORNER.bits()
+        } else {
+            0
+        };
+        let value = modifier
+            .as_ref()
+            .map_or(temp.modifier.unwrap_or(0), |modifier| modifier.value)
+            | charity_corner;
+        let use_modifier = modifier.unwrap_or_default();
+        let custom_odds = use_modifier.custom_odds.or_else(|| {
+            temp.customOdds
+                .as_ref()
+                .map(|x| serde_json::from_str(x).expect("Invalid customOdds JSON."))
+        });
+        let custom_time = use_modifier.custom_time.or_else(|| {
+            temp.customTime
+                .as_ref()
+                .map(|x| x.parse().expect("Invalid customTime."))
+        });
+        let custom_probabilities = use_modifier.custom_probabilities.or_else(|| {
+            temp.customProbabilities
+                .as_ref()
+                .map(|x| serde_json::from_str(x).expect("Invalid customProbabilities JSON."))
+        });
+        let custom_foods = use_modifier.custom_foods.or_else(|| {
+            temp.customFoods
+                .as_ref()
+                .map(|x| serde_json::from_str(x).expect("Invalid customFoods JSON."))
+        });
+        let new_modifier = Modifier::new(value, custom_odds, None)
+            .expect("Invalid modifier parameters")
+            .with_custom_time(custom_time)
+            .with_custom_probabilities(custom_probabilities)
+            .and_then(|m| m.with_custom_foods(custom_foods))
+            .expect("Invalid modifier parameters");
+
         let round_data = RoundData {
             foods: temp
                 .foods
@@ -218,16 +430,37 @@ impl NeoFoodClub {
             currentOdds: serde_json::from_str(&temp.currentOdds)
                 .expect("Invalid currentOdds JSON."),
             customOdds: None,
+            customFoods: None,
             winners: temp
                 .winners
                 .map(|x| serde_json::from_str(&x).expect("Invalid winners JSON.")),
             timestamp: temp.timestamp,
-            changes: None,
+            changes: temp
+                .changes
+                .map(|x| serde_json::from_str(&x).expect("Invalid changes JSON.")),
             lastChange: temp.lastChange,
         };
 
         NeoFoodClub::new(round_data, bet_amount, model, Some(new_modifier))
     }
+
+    /// Creates a NeoFoodClub object from a NeoFoodClub-like URL,
+    /// along with the bets and bet amounts in it, if any.
+    pub fn from_url_with_bets(
+        url: &str,
+        bet_amount: Option<u32>,
+        model: Option<ProbabilityModel>,
+        modifier: Option<Modifier>,
+    ) -> (NeoFoodClub, Option<Bets>) {
+        let nfc = NeoFoodClub::from_url(url, bet_amount, model, modifier);
+
+        let bets = nfc
+            .bets_from_url(url)
+            .expect("Invalid bets in URL.")
+            .map(|url_bets| url_bets.bets);
+
+        (nfc, bets)
+    }
 }
 
 impl NeoFoodClub {
@@ -267,6 +500,29 @@ impl NeoFoodClub {
         }
         self.winners()[0] != 0
     }
+
+    /// Samples a random set of winners from the round's probabilities.
+    /// Pass a seeded RNG for reproducible results.
+    pub fn sample_winners<R: Rng + ?Sized>(&self, rng: &mut R) -> [u8; 5] {
+        let probabilities = self.probabilities();
+        let mut winners = [0; 5];
+
+        for (arena, winner) in winners.iter_mut().enumerate() {
+            let arena_probs = &probabilities[arena][1..];
+            let mut target = rng.random::<f64>() * arena_probs.iter().sum::<f64>();
+
+            *winner = arena_probs
+                .iter()
+                .position(|&prob| {
+                    target -= prob;
+                    target < 0.0
+                })
+                .unwrap_or(arena_probs.len() - 1) as u8
+                + 1;
+        }
+
+        winners
+    }
 }
 
 impl NeoFoodClub {
@@ -336,11 +592,29 @@ impl NeoFoodClub {
         self.round_data.pirates
     }
 
-    /// Returns the changes of the round.
+    /// Returns the changes of the round, up to the modifier's custom time if it has one.
     pub fn changes(&self) -> &Option<Vec<OddsChange>> {
         &self.round_data.changes
     }
 
+    /// Returns every change of the round, including any after the modifier's custom time.
+    pub fn all_changes(&self) -> &Option<Vec<OddsChange>> {
+        &self.all_changes
+    }
+
+    /// Returns the round's odds over time, rebuilt from its opening odds and changes.
+    pub fn odds_timeline(&self) -> OddsTimeline {
+        OddsTimeline::new(&self.round_data)
+    }
+
+    /// Returns a timeline of every change of the round, including any after the modifier's custom time.
+    pub fn full_odds_timeline(&self) -> OddsTimeline {
+        OddsTimeline::new(&RoundData {
+            changes: self.all_changes.clone(),
+            ..self.round_data.clone()
+        })
+    }
+
     /// Returns the last change of the round in ISO 8601 format as a string.
     /// If the last change is not available, returns None.
     pub fn last_change(&self) -> &Option<String> {
@@ -365,6 +639,12 @@ impl NeoFoodClub {
         self.round_data.foods
     }
 
+    /// Returns the foods used for calculations, after the modifier's custom foods.
+    /// If the foods are not available, returns None.
+    pub fn custom_foods(&self) -> Option<[[u8; 10]; 5]> {
+        self.round_data.customFoods.or(self.round_data.foods)
+    }
+
     /// Returns whether or not the modifier has made changes to the round data.
     /// We use this to determine if we need to recalculate everything
     /// between
@@ -374,19 +654,25 @@ impl NeoFoodClub {
 
     /// Returns whether or not the round is outdated.
     pub fn is_outdated_lock(&self) -> bool {
-        let Some(start_date) = self.start_utc() else {
+        let (Some(start_date), Some(close)) = (self.start_utc(), self.close_utc()) else {
             return true;
         };
 
-        let day_after = start_date
-            .checked_add_signed(chrono::Duration::try_days(1).unwrap())
-            .unwrap();
+        let now = chrono::Utc::now();
 
-        let difference = get_dst_offset(day_after);
+        !(start_date <= now && now <= close)
+    }
 
-        let now = chrono::Utc::now();
+    /// Returns when the round closes in UTC, a day after it starts, adjusted for DST.
+    /// If the start time is not available, returns None.
+    pub fn close_utc(&self) -> Option<DateTime<Utc>> {
+        self.start_utc().map(close_from_start)
+    }
 
-        !(start_date <= now && now <= day_after + difference)
+    /// Returns statistics on how the odds of the whole round moved.
+    pub fn volatility(&self) -> OddsVolatility {
+        let timeline = self.odds_timeline();
+        OddsVolatility::new(timeline.changes(), self.close_utc())
     }
 
     /// Serialize the round data to JSON.
@@ -443,8 +729,25 @@ impl NeoFoodClub {
         }
     }
 
+    /// Returns max-TER indices, without dominated bets if pruning.
+    /// Only the max TER bets are built from these, the other generators use `max_ter_indices`.
+    fn pruned_max_ter_indices(&self) -> Vec<usize> {
+        let indices = self.max_ter_indices();
+
+        match self.bet_amount.filter(|_| self.prune_dominated) {
+            Some(bet_amount) => {
+                let dominating = find_dominating_sub_bets(self.round_dict_data(), bet_amount);
+                indices
+                    .into_iter()
+                    .filter(|&index| dominating[index].is_none())
+                    .collect()
+            }
+            None => indices,
+        }
+    }
+
     /// Returns max-TER indices.
-    fn max_ter_indices(&self) -> Vec<usize> {
+    pub(crate) fn max_ter_indices(&self) -> Vec<usize> {
         let use_ers = self.max_ters();
 
         let mut indices = argsort_slice_3124(use_ers, |a: &f64, b: &f64| a.total_cmp(b));
@@ -503,6 +806,82 @@ impl NeoFoodClub {
 
         data.bins[index]
     }
+
+    /// Samples `count` unique full-arena bet binaries, weighted by `weighting`.
+    ///
+    /// Each bet is picked with a weight of `exp(score / temperature)`.
+    /// For probability and ER the score is the logarithm of the value,
+    /// so at 1.0 bets are picked in proportion to their probability or ER.
+    /// For NE the score is the NE per neopoint of bet amount, which can be negative,
+    /// so at 1.0 the weights are the exponent of that instead.
+    /// Higher temperatures flatten towards uniform (`f64::INFINITY` is uniform),
+    /// and a temperature of 0.0 or lower is greedy, always picking the best bets.
+    fn sample_full_arena_binaries<R: Rng + ?Sized>(
+        &self,
+        weighting: BetWeighting,
+        temperature: f64,
+        count: usize,
+        rng: &mut R,
+    ) -> Vec<u32> {
+        let data = self.round_dict_data();
+        // the field is public, so it may not have been clamped
+        let bet_amount = self
+            .bet_amount
+            .filter(|&amount| amount >= BET_AMOUNT_MIN && !self.modifier.is_general());
+
+        // probability and ER scores are logarithms, so a temperature of 1.0 samples them
+        // proportionally, NE can be negative so it's used as is
+        let mut candidates: Vec<(u32, f64)> = (0..3124)
+            .filter(|&index| data.bins[index].count_ones() == 5)
+            .map(|index| {
+                let score = match (weighting, bet_amount) {
+                    (BetWeighting::Probability, _) => data.probs[index].ln(),
+                    (BetWeighting::NetExpected, Some(amount)) => {
+                        let maxbet = data.maxbets[index].max(BET_AMOUNT_MIN).min(amount) as f64;
+                        (maxbet * data.ers[index] - maxbet) / amount as f64
+                    }
+                    _ => data.ers[index].ln(),
+                };
+                (data.bins[index], score)
+            })
+            .collect();
+
+        // best first, so greedy picks and weight underflows are deterministic
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        if temperature <= 0.0 {
+            return candidates.iter().take(count).map(|&(bin, _)| bin).collect();
+        }
+
+        let mut chosen = Vec::<u32>::with_capacity(count);
+        while chosen.len() < count && !candidates.is_empty() {
+            let top = candidates[0].1;
+            let weights: Vec<f64> = candidates
+                .iter()
+                .map(|&(_, score)| {
+                    if temperature.is_infinite() {
+                        1.0
+                    } else {
+                        ((score - top) / temperature).exp()
+                    }
+                })
+                .collect();
+            let total: f64 = weights.iter().sum();
+
+            let mut target = rng.random::<f64>() * total;
+            let picked = weights
+                .iter()
+                .position(|&weight| {
+                    target -= weight;
+                    target < 0.0
+                })
+                .unwrap_or(0);
+
+            chosen.push(candidates.remove(picked).0);
+        }
+
+        chosen
+    }
 }
 
 impl NeoFoodClub {
@@ -517,7 +896,7 @@ impl NeoFoodClub {
     /// Creates a Bets object that consists of all max-TER bets.
     /// This is mostly for debugging purposes.
     pub fn make_all_max_ter_bets(&self) -> Bets {
-        let indices = self.max_ter_indices();
+        let indices = self.pruned_max_ter_indices();
 
         let mut bets = Bets::new(self, indices.to_vec());
         bets.fill_bet_amounts(self);
@@ -568,7 +947,7 @@ impl NeoFoodClub {
     /// Creates a Bets object that consists of max-TER bets.
     pub fn make_max_ter_bets(&self) -> Bets {
         let indices = self
-            .max_ter_indices()
+            .pruned_max_ter_indices()
             .iter()
             .take(self.max_amount_of_bets())
             .cloned()
@@ -642,6 +1021,49 @@ impl NeoFoodClub {
         bets
     }
 
+    /// Creates a Bets object that consists of "crazy" bets,
+    /// sampled by their probability, ER or NE instead of uniformly.
+    /// See `BetWeighting` for the options, the temperature goes from greedy (0.0)
+    /// through proportional to the probability or ER (1.0) to uniform (`f64::INFINITY`).
+    pub fn make_weighted_crazy_bets(&self, weighting: BetWeighting, temperature: f64) -> Bets {
+        self.make_weighted_crazy_bets_with_rng(weighting, temperature, &mut rand::rng())
+    }
+
+    /// Same as `make_weighted_crazy_bets`, with the given RNG.
+    /// Pass a seeded RNG for reproducible results.
+    pub fn make_weighted_crazy_bets_with_rng<R: Rng + ?Sized>(
+        &self,
+        weighting: BetWeighting,
+        temperature: f64,
+        rng: &mut R,
+    ) -> Bets {
+        let binaries =
+            self.sample_full_arena_binaries(weighting, temperature, self.max_amount_of_bets(), rng);
+
+        let mut bets = Bets::from_binaries(self, binaries);
+        bets.fill_bet_amounts(self);
+        bets
+    }
+
+    /// Picks a full-arena bet by its probability, ER or NE and makes a gambit out of it.
+    /// The temperature works the same as in `make_weighted_crazy_bets`.
+    pub fn make_weighted_gambit_bets(&self, weighting: BetWeighting, temperature: f64) -> Bets {
+        self.make_weighted_gambit_bets_with_rng(weighting, temperature, &mut rand::rng())
+    }
+
+    /// Same as `make_weighted_gambit_bets`, with the given RNG.
+    /// Pass a seeded RNG for reproducible results.
+    pub fn make_weighted_gambit_bets_with_rng<R: Rng + ?Sized>(
+        &self,
+        weighting: BetWeighting,
+        temperature: f64,
+        rng: &mut R,
+    ) -> Bets {
+        let binaries = self.sample_full_arena_binaries(weighting, temperature, 1, rng);
+
+        self.make_gambit_bets(binaries[0])
+    }
+
     /// Creates a Bets object that consists of bustproof bets.
     /// Returns None if there are no positive arenas.
     pub fn make_bustproof_bets(&self) -> Option<Bets> {
@@ -723,8 +1145,10 @@ impl NeoFoodClub {
                 let odds = bets.odds_values(self);
                 let lowest = odds.iter().min().expect("Odds vector is empty, somehow");
 
-                let bet_amounts: Vec<Option<u32>> =
-                    odds.iter().map(|odd| Some(amount * lowest / odd)).collect();
+                let bet_amounts: Vec<Option<u32>> = odds
+                    .iter()
+                    .map(|&odd| Some((amount as u64 * *lowest as u64 / odd as u64) as u32))
+                    .collect();
 
                 bets.bet_amounts = Some(bet_amounts);
             }
@@ -821,54 +1245,50 @@ impl NeoFoodClub {
     /// Returns the amount of units you'd win if you placed the given bets.
     /// Returns 0 if there are no winners yet.
     pub fn get_win_units(&self, bets: &Bets) -> u32 {
-        let winners_binary = self.winners_binary();
-
-        if winners_binary == 0 {
+        if self.winners_binary() == 0 {
             return 0;
         }
-        let data = self.round_dict_data();
 
-        bets.array_indices
-            .iter()
-            .map(|i| {
-                let bet_bin = data.bins[*i];
-
-                if bet_bin & winners_binary == bet_bin {
-                    data.odds[*i]
-                } else {
-                    0
-                }
-            })
-            .sum()
+        self.evaluate_outcome(bets, self.winners()).units
     }
 
     /// Returns the amount of neopoints you'd win if you placed the given bets.
     /// Returns 0 if there are no winners yet.
     /// Returns 0 if there are no bet amounts.
     pub fn get_win_np(&self, bets: &Bets) -> u32 {
-        let Some(bet_amounts) = bets.bet_amounts.as_ref() else {
+        if self.winners_binary() == 0 {
             return 0;
-        };
+        }
 
-        let winners_binary = self.winners_binary();
+        self.evaluate_outcome(bets, self.winners()).np.unwrap_or(0)
+    }
 
-        if winners_binary == 0 {
-            return 0;
+    /// Returns how each of the given bets did against the round's winners.
+    /// Returns None if there are no winners yet.
+    pub fn bet_results(&self, bets: &Bets) -> Option<Vec<BetResult>> {
+        if self.winners_binary() == 0 {
+            return None;
         }
-        let data = self.round_dict_data();
 
-        bets.array_indices
-            .iter()
-            .enumerate()
-            .fold(0, |acc, (bet_index, array_index)| {
-                let bet_bin = data.bins[*array_index];
-                if bet_bin & winners_binary == bet_bin {
-                    acc + (data.odds[*array_index] * bet_amounts[bet_index].unwrap_or(0))
-                        .clamp(0, 1_000_000)
-                } else {
-                    acc
-                }
-            })
+        Some(
+            self.evaluate_outcome(bets, self.winners())
+                .bet_results(self, bets),
+        )
+    }
+
+    /// Scores the given bets as if the given pirate indices had won,
+    /// regardless of the round's actual winners.
+    /// Panics if any winner is not 1-4.
+    pub fn evaluate_outcome(&self, bets: &Bets, winners: [u8; 5]) -> Outcome {
+        Outcome::new(self, bets, winners)
+    }
+
+    /// Scores the given bets against every possible set of winners, 1024 in total.
+    pub fn payoff_table(&self, bets: &Bets) -> Vec<Outcome> {
+        winner_combinations()
+            .into_iter()
+            .map(|winners| self.evaluate_outcome(bets, winners))
+            .collect()
     }
 }
 
@@ -898,6 +1318,36 @@ impl NeoFoodClub {
             }
         }
 
+        let flags = self.modifier.value & !ModifierFlags::CHARITY_CORNER.bits();
+        let modifier_params = UrlModifierParams {
+            modifier: (flags != 0).then_some(flags),
+            custom_odds: self.modifier.custom_odds.as_ref().map(|custom_odds| {
+                // sorted, so the same modifier always makes the same URL
+                let sorted: BTreeMap<&u8, &u8> = custom_odds.iter().collect();
+                serde_json::to_string(&sorted).expect("Failed to serialize customOdds.")
+            }),
+            custom_time: self.modifier.custom_time.map(|t| t.to_string()),
+            custom_probabilities: self.modifier.custom_probabilities.as_ref().map(
+                |custom_probabilities| {
+                    let sorted: BTreeMap<&u8, &ProbabilityOverride> =
+                        custom_probabilities.iter().collect();
+                    serde_json::to_string(&sorted)
+                        .expect("Failed to serialize customProbabilities.")
+                },
+            ),
+            custom_foods: self.modifier.custom_foods.as_ref().map(|custom_foods| {
+                let sorted: BTreeMap<&u8, &[u8; 10]> = custom_foods.iter().collect();
+                serde_json::to_string(&sorted).expect("Failed to serialize customFoods.")
+            }),
+        };
+
+        let qs =
+            serde_qs::to_string(&modifier_params).expect("Failed to serialize URL query string.");
+        if !qs.is_empty() {
+            url.push('&');
+            url.push_str(&qs);
+        }
+
         if all_data {
             let pirates = serde_json::to_string(&self.round_data.pirates)
                 .expect("Failed to serialize pirates.");
@@ -905,6 +1355,14 @@ impl NeoFoodClub {
                 .expect("Failed to serialize openingOdds.");
             let current_odds = serde_json::to_string(&self.round_data.currentOdds)
                 .expect("Failed to serialize currentOdds.");
+            let foods = self
+                .round_data
+                .foods
+                .map(|foods| serde_json::to_string(&foods).expect("Failed to serialize foods."));
+            // the custom time is in the URL too, so every change is kept
+            let changes = self.all_changes.as_ref().map(|changes| {
+                serde_json::to_string(changes).expect("Failed to serialize changes.")
+            });
             let winners = if self.is_over() {
                 Some(serde_json::to_string(&self.winners()).expect("Failed to serialize winners."))
             } else {
@@ -915,8 +1373,12 @@ impl NeoFoodClub {
                 pirates: &pirates,
                 opening_odds: &opening_odds,
                 current_odds: &current_odds,
+                foods: foods.as_deref(),
                 winners: winners.as_deref(),
                 timestamp: self.timestamp().as_ref().map(|s| s.as_str()),
+                start: self.round_data.start.as_deref(),
+                last_change: self.round_data.lastChange.as_deref(),
+                changes: changes.as_deref(),
             };
 
             let qs = serde_qs::to_string(&params).expect("Failed to serialize URL query string.");
@@ -927,19 +1389,162 @@ impl NeoFoodClub {
         url
     }
 
+    /// Reads the bets hash (`b`) and amounts hash (`a`) from a NeoFoodClub-like URL.
+    /// Returns None if the URL has no bets.
+    /// Returns an error if the URL is for a different round, or the hashes are invalid.
+    pub fn bets_from_url(&self, url: &str) -> Result<Option<UrlBets>, String> {
+        let Some((path, fragment)) = url.split_once('#') else {
+            return Err("No relevant NeoFoodClub-like URL data found.".to_string());
+        };
+
+        let temp: UrlBetsRaw = serde_qs::from_str(fragment).map_err(|e| e.to_string())?;
+
+        if let Some(round) = temp.round {
+            if round != self.round() {
+                return Err(format!(
+                    "URL is for round {round}, not round {}.",
+                    self.round()
+                ));
+            }
+        }
+
+        let Some(hash) = temp.b.filter(|b| !b.is_empty()) else {
+            return Ok(None);
+        };
+
+        let mut bets = Bets::from_hash(self, &hash)?;
+
+        if let Some(amounts_hash) = temp.a.filter(|a| !a.is_empty()) {
+            bets.set_bet_amounts(&Some(BetAmounts::AmountHash(amounts_hash)))?;
+        }
+
+        let mut warnings = vec![];
+
+        let max_bets = if path.ends_with("/15/") {
+            15
+        } else {
+            self.max_amount_of_bets()
+        };
+        if bets.len() > max_bets {
+            warnings.push(format!(
+                "URL has {} bets, but only {max_bets} can be placed.",
+                bets.len()
+            ));
+        }
+
+        Ok(Some(UrlBets { bets, warnings }))
+    }
+
+    /// Compares two sets of bets on this round, over every possible set of winners.
+    pub fn compare_bets(&self, a: &Bets, b: &Bets) -> BetsComparison {
+        BetsComparison::new(self, a, b)
+    }
+
+    /// Plays the given bets over `rounds` simulated rounds, sampling the winners of each.
+    /// Pass a seeded RNG for reproducible results.
+    pub fn simulate<R: Rng + ?Sized>(&self, bets: &Bets, rounds: u32, rng: &mut R) -> Simulation {
+        Simulation::new(self, bets, rounds, rng)
+    }
+
     /// Creates a deep copy of the NeoFoodClub object.
     /// If `model` is None, the model is going to use the default.
     /// If `modifier` is None, the modifier is going to be empty.
     pub fn copy(&self, model: Option<ProbabilityModel>, modifier: Option<Modifier>) -> NeoFoodClub {
         let mut round_data = self.round_data.clone();
         round_data.customOdds = None;
-        NeoFoodClub::new(round_data, self.bet_amount, model, modifier)
+        round_data.customFoods = None;
+        round_data.changes = self.all_changes.clone();
+        let mut nfc = NeoFoodClub::new(round_data, self.bet_amount, model, modifier);
+        nfc.prune_dominated = self.prune_dominated;
+        nfc
+    }
+
+    /// Returns a copy of this round as it was at the given odds snapshot,
+    /// with the snapshot's odds as the current odds and only the changes made up to it.
+    /// The modifier is kept, except for its custom time and opening odds flag.
+    pub fn at_snapshot(&self, snapshot: &OddsSnapshot) -> NeoFoodClub {
+        let mut round_data = self.round_data.clone();
+        round_data.customOdds = None;
+        round_data.customFoods = None;
+        round_data.currentOdds = snapshot.odds;
+        round_data.changes = snapshot.timestamp.and_then(|timestamp| {
+            let changes = self
+                .odds_timeline()
+                .changes_until(timestamp)
+                .cloned()
+                .collect::<Vec<_>>();
+            (!changes.is_empty()).then_some(changes)
+        });
+
+        let modifier = Modifier {
+            value: self.modifier.value & !ModifierFlags::OPENING_ODDS.bits(),
+            custom_time: None,
+            ..self.modifier.clone()
+        };
+
+        let mut nfc = NeoFoodClub::new(
+            round_data,
+            self.bet_amount,
+            Some(self.probability_model.clone()),
+            Some(modifier),
+        );
+        nfc.prune_dominated = self.prune_dominated;
+        nfc
+    }
+
+    /// Re-evaluates the given bets at every odds snapshot of the round.
+    pub fn bets_history(&self, bets: &Bets) -> BetsHistory {
+        BetsHistory::new(self, bets)
+    }
+
+    /// Re-evaluates the given bets, or the max TER set if None,
+    /// with each pirate's odds moved by 1 or 2 either way.
+    pub fn odds_sensitivity(&self, bets: Option<&Bets>) -> OddsSensitivity {
+        OddsSensitivity::new(self, bets)
     }
+
+    /// Recomputes the given strategy's bets at every odds snapshot of the round.
+    pub fn bet_timing<F>(&self, strategy: F) -> BetTiming
+    where
+        F: Fn(&NeoFoodClub) -> Bets,
+    {
+        BetTiming::new(self, strategy)
+    }
+}
+
+/// Adds the new changes that aren't in the log yet, keeping it sorted by time.
+/// A change is identified by when it happened, and to which pirate.
+fn merge_changes(changes: &mut Vec<OddsChange>, new_changes: &[OddsChange]) {
+    let key = |change: &OddsChange| {
+        (
+            change.timestamp_utc(),
+            change.arena_index(),
+            change.pirate_index(),
+        )
+    };
+
+    let mut seen: HashSet<(DateTime<Utc>, usize, usize)> = changes.iter().map(key).collect();
+    changes.extend(
+        new_changes
+            .iter()
+            .filter(|change| seen.insert(key(change)))
+            .cloned(),
+    );
+
+    // stable, so changes sharing a timestamp keep their order
+    changes.sort_by_key(OddsChange::timestamp_utc);
 }
 
 fn validate_round_data(round_data: &RoundData) {
+    if let Err(error) = check_round_data(round_data) {
+        panic!("{error}");
+    }
+}
+
+/// Returns an error describing the first thing wrong with the round data, if anything.
+fn check_round_data(round_data: &RoundData) -> Result<(), String> {
     if round_data.round == 0 {
-        panic!("Round number must be greater than 0.");
+        return Err("Round number must be greater than 0.".to_string());
     }
 
     let mut pirate_ids = Vec::<u8>::with_capacity(20);
@@ -947,10 +1552,10 @@ fn validate_round_data(round_data: &RoundData) {
     for arena in round_data.pirates.iter() {
         for pirate in arena.iter() {
             if pirate_ids.contains(pirate) {
-                panic!("Pirates must be unique.");
+                return Err("Pirates must be unique.".to_string());
             }
             if !(&1..=&20).contains(&pirate) {
-                panic!("Pirate IDs must be between 1 and 20.");
+                return Err("Pirate IDs must be between 1 and 20.".to_string());
             }
             pirate_ids.push(*pirate);
         }
@@ -960,10 +1565,10 @@ fn validate_round_data(round_data: &RoundData) {
         for (index, odds) in arena.iter().enumerate() {
             if index == 0 {
                 if *odds != 1 {
-                    panic!("First integer in each arena in currentOdds must be 1.");
+                    return Err("First integer in each arena in currentOdds must be 1.".to_string());
                 }
             } else if *odds < 2 || *odds > 13 {
-                panic!("Odds must be between 2 and 13.");
+                return Err("Odds must be between 2 and 13.".to_string());
             }
         }
     }
@@ -972,10 +1577,10 @@ fn validate_round_data(round_data: &RoundData) {
         for (index, odds) in arena.iter().enumerate() {
             if index == 0 {
                 if *odds != 1 {
-                    panic!("First integer in each arena in openingOdds must be 1.");
+                    return Err("First integer in each arena in openingOdds must be 1.".to_string());
                 }
             } else if *odds < 2 || *odds > 13 {
-                panic!("Odds must be between 2 and 13.");
+                return Err("Odds must be between 2 and 13.".to_string());
             }
         }
     }
@@ -984,7 +1589,7 @@ fn validate_round_data(round_data: &RoundData) {
         for arena in foods.iter() {
             for food in arena.iter() {
                 if *food < 1 || *food > 40 {
-                    panic!("Food integers must be between 1 and 40.");
+                    return Err("Food integers must be between 1 and 40.".to_string());
                 }
             }
         }
@@ -996,7 +1601,9 @@ fn validate_round_data(round_data: &RoundData) {
         let all_one_to_four = winners.iter().all(|&x| (1..=4).contains(&x));
 
         if !(all_zero ^ all_one_to_four) {
-            panic!("Winners must either be all 0, or all 1-4.");
+            return Err("Winners must either be all 0, or all 1-4.".to_string());
         }
     }
+
+    Ok(())
 }
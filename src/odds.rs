@@ -1,6 +1,6 @@
 use crate::{
-    chance::Chance,
-    math::{binary_to_indices, build_chance_objects},
+    chance::{Chance, NpChance},
+    math::{bet_payout, binary_to_indices, build_chance_objects},
     nfc::NeoFoodClub,
 };
 
@@ -15,17 +15,26 @@ pub struct Odds {
 
 impl Odds {
     pub fn new(nfc: &NeoFoodClub, array_indices: &[usize]) -> Self {
-        let (pirate_indices, odds_values): (Vec<[u8; 5]>, Vec<u32>) = array_indices
+        let odds_values: Vec<u32> = array_indices
             .iter()
-            .map(|&index| {
-                (
-                    binary_to_indices(nfc.round_dict_data().bins[index]),
-                    nfc.round_dict_data().odds[index],
-                )
-            })
-            .unzip();
+            .map(|&index| nfc.round_dict_data().odds[index])
+            .collect();
+
+        Self::with_odds_values(nfc, array_indices, &odds_values)
+    }
+
+    /// Like `new`, but each bet pays out at the given odds instead of the round's.
+    pub fn with_odds_values(
+        nfc: &NeoFoodClub,
+        array_indices: &[usize],
+        odds_values: &[u32],
+    ) -> Self {
+        let pirate_indices: Vec<[u8; 5]> = array_indices
+            .iter()
+            .map(|&index| binary_to_indices(nfc.round_dict_data().bins[index]))
+            .collect();
 
-        let chances = build_chance_objects(&pirate_indices, &odds_values, nfc.probabilities());
+        let chances = build_chance_objects(&pirate_indices, odds_values, nfc.probabilities());
 
         Self {
             chances,
@@ -65,8 +74,254 @@ impl Odds {
             .sum()
     }
 
+    /// The mean amount of units won.
+    pub fn mean(&self) -> f64 {
+        self.chances
+            .iter()
+            .map(|c| c.value as f64 * c.probability)
+            .sum()
+    }
+
+    /// The variance of the units won.
+    pub fn variance(&self) -> f64 {
+        let mean = self.mean();
+
+        self.chances
+            .iter()
+            .map(|c| (c.value as f64 - mean).powi(2) * c.probability)
+            .sum()
+    }
+
+    /// The standard deviation of the units won.
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// The median amount of units won.
+    pub fn median(&self) -> u32 {
+        self.quantile(0.5)
+    }
+
+    /// The smallest amount of units won with at least a `q` chance of winning that much or less.
+    /// `q` is clamped between 0 and 1.
+    pub fn quantile(&self, q: f64) -> u32 {
+        let q = q.clamp(0.0, 1.0);
+
+        let index = self
+            .chances
+            .partition_point(|c| c.cumulative < q)
+            .min(self.chances.len() - 1);
+
+        self.chances[index].value
+    }
+
+    /// The probability of winning at least `units` units.
+    pub fn probability_at_least(&self, units: u32) -> f64 {
+        let index = self.chances.partition_point(|c| c.value < units);
+
+        self.chances.get(index).map_or(0.0, |c| c.tail)
+    }
+
+    /// The probability of winning more units than there are bets,
+    /// as in, making a profit if every bet has the same amount.
+    pub fn probability_of_profit(&self) -> f64 {
+        self.probability_at_least(self.amount_of_bets + 1)
+    }
+
+    /// The average amount of units won in the worst `alpha` share of outcomes.
+    /// `alpha` is clamped between 0 and 1, and 0 returns the worst outcome.
+    pub fn expected_shortfall(&self, alpha: f64) -> f64 {
+        let alpha = alpha.clamp(0.0, 1.0);
+
+        if alpha == 0.0 {
+            return self.chances[0].value as f64;
+        }
+
+        let mut remaining = alpha;
+        let mut total = 0.0;
+        for chance in self.chances.iter() {
+            let taken = chance.probability.min(remaining);
+            total += chance.value as f64 * taken;
+            remaining -= taken;
+
+            if remaining <= 0.0 {
+                break;
+            }
+        }
+
+        total / (alpha - remaining.max(0.0))
+    }
+
+    /// The Kelly-optimal fraction of a bankroll to stake on this set,
+    /// assuming the same amount is placed on every bet.
+    /// Returns 0.0 if the set isn't expected to profit.
+    pub fn kelly_fraction(&self) -> f64 {
+        if self.amount_of_bets == 0 {
+            return 0.0;
+        }
+
+        // the return per NP staked, for each outcome
+        let bets = self.amount_of_bets as f64;
+        let returns: Vec<(f64, f64)> = self
+            .chances
+            .iter()
+            .map(|c| (c.value as f64 / bets - 1.0, c.probability))
+            .collect();
+
+        kelly_fraction(&returns)
+    }
+
     /// Returns a reference to the vector of Chance objects.
     pub fn chances(&self) -> &[Chance] {
         &self.chances
     }
 }
+
+/// Like `Odds`, but in neopoints, respecting each bet's amount and the 1,000,000 NP payout cap.
+#[derive(Debug, Clone)]
+pub struct NpOdds {
+    /// A vector of NpChance objects, sorted by payout from least to greatest.
+    chances: Vec<NpChance>,
+
+    /// The total amount of neopoints staked.
+    total_stake: u32,
+}
+
+impl NpOdds {
+    /// Each bet pays out at its given odds value.
+    /// Missing bet amounts are treated as 0 NP.
+    pub fn new(
+        nfc: &NeoFoodClub,
+        array_indices: &[usize],
+        odds_values: &[u32],
+        bet_amounts: &[Option<u32>],
+    ) -> Self {
+        let data = nfc.round_dict_data();
+
+        let (pirate_indices, payouts): (Vec<[u8; 5]>, Vec<u32>) = array_indices
+            .iter()
+            .zip(odds_values.iter())
+            .enumerate()
+            .map(|(bet_index, (&index, &odds))| {
+                let amount = bet_amounts.get(bet_index).copied().flatten().unwrap_or(0);
+                (
+                    binary_to_indices(data.bins[index]),
+                    bet_payout(odds, amount),
+                )
+            })
+            .unzip();
+
+        let total_stake = bet_amounts.iter().map(|a| a.unwrap_or(0)).sum::<u32>();
+
+        let chances = build_chance_objects(&pirate_indices, &payouts, nfc.probabilities())
+            .into_iter()
+            .map(|c| NpChance {
+                payout: c.value,
+                profit: c.value as i64 - total_stake as i64,
+                probability: c.probability,
+                cumulative: c.cumulative,
+                tail: c.tail,
+            })
+            .collect();
+
+        Self {
+            chances,
+            total_stake,
+        }
+    }
+
+    /// The total amount of neopoints staked.
+    pub fn total_stake(&self) -> u32 {
+        self.total_stake
+    }
+
+    /// The NpChance object with the highest payout.
+    pub fn best(&self) -> NpChance {
+        self.chances
+            .last()
+            .expect("Chances vector should not be empty")
+            .clone()
+    }
+
+    /// The NpChance object for busting. Can be None if this bet set is bustproof.
+    pub fn bust(&self) -> Option<NpChance> {
+        self.chances.first().filter(|c| c.payout == 0).cloned()
+    }
+
+    /// The mean neopoints paid out.
+    pub fn mean_payout(&self) -> f64 {
+        self.chances
+            .iter()
+            .map(|c| c.payout as f64 * c.probability)
+            .sum()
+    }
+
+    /// The mean neopoints profit, which is negative on average for losing sets.
+    pub fn mean_profit(&self) -> f64 {
+        self.mean_payout() - self.total_stake as f64
+    }
+
+    /// The probability of being paid out more than was staked.
+    pub fn probability_of_profit(&self) -> f64 {
+        self.chances
+            .iter()
+            .find(|c| c.profit > 0)
+            .map_or(0.0, |c| c.tail)
+    }
+
+    /// Returns a reference to the vector of NpChance objects.
+    pub fn chances(&self) -> &[NpChance] {
+        &self.chances
+    }
+
+    /// The Kelly-optimal fraction of a bankroll to stake on this set,
+    /// keeping the ratios between the bet amounts.
+    /// Returns 0.0 if the set isn't expected to profit.
+    pub fn kelly_fraction(&self) -> f64 {
+        if self.total_stake == 0 {
+            return 0.0;
+        }
+
+        // the return per NP staked, for each outcome
+        let stake = self.total_stake as f64;
+        let returns: Vec<(f64, f64)> = self
+            .chances
+            .iter()
+            .map(|c| (c.payout as f64 / stake - 1.0, c.probability))
+            .collect();
+
+        kelly_fraction(&returns)
+    }
+}
+
+/// Finds the fraction that maximizes the expected log growth,
+/// given the return per NP staked and the probability of each outcome.
+fn kelly_fraction(returns: &[(f64, f64)]) -> f64 {
+    // derivative of the expected log growth, which decreases as the fraction grows
+    let growth = |fraction: f64| -> f64 {
+        returns
+            .iter()
+            .map(|(r, p)| p * r / (1.0 + fraction * r))
+            .sum()
+    };
+
+    if growth(0.0) <= 0.0 {
+        return 0.0;
+    }
+
+    let (mut low, mut high) = (0.0, 1.0 - 1e-9);
+    if growth(high) > 0.0 {
+        return high;
+    }
+
+    for _ in 0..64 {
+        let mid = (low + high) / 2.0;
+        if growth(mid) > 0.0 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    low
+}
@@ -20,6 +20,18 @@ pub struct OddsChange {
 }
 
 impl OddsChange {
+    /// Creates a new change. `t` is an ISO 8601 timestamp,
+    /// `arena` is the arena index (0-4) and `pirate` is the pirate index (1-4).
+    pub fn new(t: String, new: u8, old: u8, arena: u8, pirate: u8) -> Self {
+        Self {
+            t,
+            new,
+            old,
+            arena,
+            pirate,
+        }
+    }
+
     /// Returns the pirate associated with the change.
     pub fn pirate(&self, nfc: &NeoFoodClub) -> PartialPirate {
         PartialPirate {
@@ -49,6 +61,12 @@ impl OddsChange {
         self.arena as usize
     }
 
+    /// Returns how much the odds moved, positive if they went up.
+    #[inline]
+    pub fn difference(&self) -> i8 {
+        self.new as i8 - self.old as i8
+    }
+
     /// Returns the timestamp of the change in NST.
     pub fn timestamp_nst(&self) -> DateTime<Tz> {
         convert_from_utc_to_nst(self.timestamp_utc())
@@ -0,0 +1,136 @@
+use crate::{
+    bets::Bets,
+    math::{bet_payout, pirates_binary, winners_probability},
+    nfc::NeoFoodClub,
+};
+
+/// What a set of bets pays out for one possible set of winners.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    /// The winning pirate index of each arena.
+    pub winners: [u8; 5],
+
+    /// The probability of these winners.
+    pub probability: f64,
+
+    /// Amount of units won.
+    pub units: u32,
+
+    /// Amount of neopoints won.
+    /// None if the bets have no bet amounts.
+    pub np: Option<u32>,
+
+    /// Whether or not each bet hit, in the order of the bets.
+    pub hits: Vec<bool>,
+}
+
+impl Outcome {
+    /// Scores the given bets against the given winners.
+    pub fn new(nfc: &NeoFoodClub, bets: &Bets, winners: [u8; 5]) -> Self {
+        if !winners.iter().all(|&w| (1..=4).contains(&w)) {
+            panic!("Winners must all be 1-4.");
+        }
+
+        let data = nfc.round_dict_data();
+        let winners_binary = pirates_binary(winners);
+
+        let hits: Vec<bool> = bets
+            .array_indices
+            .iter()
+            .map(|&array_index| {
+                let bet_bin = data.bins[array_index];
+                bet_bin & winners_binary == bet_bin
+            })
+            .collect();
+
+        let odds_values = bets.odds_values(nfc);
+
+        let units = odds_values
+            .iter()
+            .zip(hits.iter())
+            .filter(|(_, &hit)| hit)
+            .map(|(&odds, _)| odds)
+            .sum();
+
+        let np = bets.bet_amounts.as_ref().map(|amounts| {
+            odds_values
+                .iter()
+                .zip(amounts.iter())
+                .zip(hits.iter())
+                .filter(|(_, &hit)| hit)
+                .map(|((&odds, amount), _)| bet_payout(odds, amount.unwrap_or(0)))
+                .sum()
+        });
+
+        Self {
+            winners,
+            probability: winners_probability(winners, nfc.probabilities()),
+            units,
+            np,
+            hits,
+        }
+    }
+
+    /// Amount of bets that hit.
+    pub fn hit_count(&self) -> usize {
+        self.hits.iter().filter(|&&hit| hit).count()
+    }
+
+    /// Breaks this outcome down into how each bet did.
+    pub fn bet_results(&self, nfc: &NeoFoodClub, bets: &Bets) -> Vec<BetResult> {
+        let amounts = bets.bet_amounts.as_deref().unwrap_or_default();
+
+        bets.odds_values(nfc)
+            .into_iter()
+            .zip(self.hits.iter())
+            .enumerate()
+            .map(|(bet_index, (odds, &hit))| {
+                let stake = amounts.get(bet_index).copied().flatten().unwrap_or(0);
+                let (raw_payout, payout) = if hit {
+                    (odds as u64 * stake as u64, bet_payout(odds, stake))
+                } else {
+                    (0, 0)
+                };
+
+                BetResult {
+                    hit,
+                    odds,
+                    stake,
+                    raw_payout,
+                    payout,
+                }
+            })
+            .collect()
+    }
+}
+
+/// How a single bet did against a round's winners.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BetResult {
+    /// Whether or not the bet hit.
+    pub hit: bool,
+
+    /// The bet's odds.
+    pub odds: u32,
+
+    /// The bet's amount, 0 if it has none.
+    pub stake: u32,
+
+    /// The neopoints the bet would pay out without the 1,000,000 NP cap.
+    pub raw_payout: u64,
+
+    /// The neopoints the bet actually pays out.
+    pub payout: u32,
+}
+
+impl BetResult {
+    /// The neopoints lost to the 1,000,000 NP payout cap.
+    pub fn lost_to_cap(&self) -> u64 {
+        self.raw_payout - self.payout as u64
+    }
+
+    /// The neopoints gained or lost on this bet.
+    pub fn profit(&self) -> i64 {
+        self.payout as i64 - self.stake as i64
+    }
+}
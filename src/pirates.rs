@@ -2,6 +2,7 @@ use crate::{
     food_adjustments::{NEGATIVE_FOOD, POSITIVE_FOOD},
     math::pirate_binary,
     nfc::NeoFoodClub,
+    volatility::OddsVolatility,
 };
 
 /// A list of pirate names.
@@ -82,7 +83,7 @@ impl Pirate {
 
     /// The pirates positive foods for a given NFC object.
     pub fn positive_foods(&self, nfc: &NeoFoodClub) -> Option<Vec<u8>> {
-        if let Some(nfc_foods) = nfc.foods() {
+        if let Some(nfc_foods) = nfc.custom_foods() {
             let foods: Vec<u8> = nfc_foods[self.arena_id as usize]
                 .iter()
                 .filter(|&food| POSITIVE_FOOD[self.id as usize - 1][*food as usize - 1] != 0)
@@ -98,7 +99,7 @@ impl Pirate {
 
     /// The pirates negative foods for a given NFC object.
     pub fn negative_foods(&self, nfc: &NeoFoodClub) -> Option<Vec<u8>> {
-        if let Some(nfc_foods) = nfc.foods() {
+        if let Some(nfc_foods) = nfc.custom_foods() {
             let foods: Vec<u8> = nfc_foods[self.arena_id as usize]
                 .iter()
                 .filter(|&food| NEGATIVE_FOOD[self.id as usize - 1][*food as usize - 1] != 0)
@@ -111,6 +112,39 @@ impl Pirate {
         }
         None
     }
+
+    /// The smallest win probability that makes betting on the pirate alone +EV at its current odds,
+    /// for a given NFC object.
+    pub fn break_even_probability(&self, nfc: &NeoFoodClub) -> f64 {
+        nfc.round_dict_data()
+            .break_even_probability(self.bet_index(nfc))
+    }
+
+    /// How far the model's probability of the pirate winning is above its break-even probability,
+    /// which is how wrong the model can be before betting on the pirate alone stops being +EV.
+    /// Negative if the pirate alone is -EV.
+    pub fn break_even_margin(&self, nfc: &NeoFoodClub) -> f64 {
+        nfc.round_dict_data().break_even_margin(self.bet_index(nfc))
+    }
+
+    /// The index of the bet on the pirate alone in the NFC object's `RoundDictData`.
+    fn bet_index(&self, nfc: &NeoFoodClub) -> usize {
+        let binary = self.binary();
+        nfc.round_dict_data()
+            .bins
+            .iter()
+            .position(|&bin| bin == binary)
+            .expect("Every pirate has a bet of its own")
+    }
+
+    /// Statistics on how the pirate's odds moved, for a given NFC object.
+    pub fn volatility(&self, nfc: &NeoFoodClub) -> OddsVolatility {
+        OddsVolatility::new(
+            nfc.odds_timeline()
+                .changes_for(self.arena_id as usize, self.index as usize),
+            nfc.close_utc(),
+        )
+    }
 }
 
 macro_rules! impl_partial_pirate_things {
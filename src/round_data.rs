@@ -1,4 +1,6 @@
+use crate::diff::RoundDiff;
 use crate::oddschange::OddsChange;
+use crate::timeline::OddsTimeline;
 use crate::utils::{convert_from_utc_to_nst, timestamp_to_utc};
 use chrono::{DateTime, Utc};
 use chrono_tz::Tz;
@@ -13,6 +15,7 @@ pub struct RoundData {
     pub pirates: [[u8; 4]; 5],
     pub currentOdds: [[u8; 5]; 5],
     pub customOdds: Option<[[u8; 5]; 5]>,
+    pub customFoods: Option<[[u8; 10]; 5]>,
     pub openingOdds: [[u8; 5]; 5],
     pub winners: Option<[u8; 5]>,
     pub timestamp: Option<String>,
@@ -66,4 +69,20 @@ impl RoundData {
             .as_ref()
             .map(|timestamp| timestamp_to_utc(timestamp))
     }
+
+    /// Compares this snapshot against a newer one of the same round,
+    /// stamping any odds changes with the given time.
+    /// Returns an error if the snapshots are from different rounds.
+    pub fn diff(&self, newer: &RoundData, timestamp: DateTime<Utc>) -> Result<RoundDiff, String> {
+        RoundDiff::new(self, newer, timestamp)
+    }
+
+    /// Returns the odds as they were at the given time,
+    /// by applying every change up to and including it to the opening odds.
+    /// If there are no changes, returns None.
+    pub fn odds_at(&self, timestamp: DateTime<Utc>) -> Option<[[u8; 5]; 5]> {
+        self.changes.as_ref()?;
+
+        Some(OddsTimeline::new(self).odds_at(timestamp))
+    }
 }
@@ -0,0 +1,160 @@
+use comfy_table::Table;
+use itertools::Itertools;
+
+use crate::{
+    bets::Bets,
+    modifier::Modifier,
+    nfc::NeoFoodClub,
+    pirates::{PartialPirateThings, Pirate},
+};
+
+/// How far each pirate's odds are moved, in both directions.
+const ODDS_STEPS: [i8; 4] = [-2, -1, 1, 2];
+
+/// A set of bets' value if one pirate's odds had moved.
+#[derive(Debug, Clone)]
+pub struct SensitivityPoint {
+    /// The pirate whose odds moved, with its odds before the move.
+    pub pirate: Pirate,
+
+    /// The pirate's odds after the move. 2-13 inclusive.
+    pub new_odds: u8,
+
+    /// The expected return of the bets at the new odds.
+    pub expected_return: f64,
+
+    /// The net expected value of the bets at the new odds, 0 if they have no bet amounts.
+    pub net_expected: f64,
+
+    /// The probability of the bets busting at the new odds.
+    pub bust_probability: f64,
+
+    /// Whether or not the max TER set at the new odds differs from the current one, ignoring order.
+    pub changes_max_ter: bool,
+}
+
+impl SensitivityPoint {
+    /// How much the odds moved. Negative if they went down.
+    pub fn difference(&self) -> i8 {
+        self.new_odds as i8 - self.pirate.current_odds as i8
+    }
+}
+
+/// How a set of bets' value changes if each pirate's odds move by 1 or 2 either way,
+/// to see which odds changes matter before they happen.
+#[derive(Debug, Clone)]
+pub struct OddsSensitivity {
+    /// The expected return of the bets at the current odds.
+    pub expected_return: f64,
+
+    /// The net expected value of the bets at the current odds, 0 if they have no bet amounts.
+    pub net_expected: f64,
+
+    /// The probability of the bets busting at the current odds.
+    pub bust_probability: f64,
+
+    /// One point per pirate and odds move that stays within 2-13,
+    /// in order of arena, pirate, then new odds.
+    pub points: Vec<SensitivityPoint>,
+}
+
+impl OddsSensitivity {
+    /// Evaluates the given bets, keeping their bet amounts, with each pirate's odds moved
+    /// through the modifier's custom odds, see `Bets::rebuild`.
+    /// If no bets are given, the current max TER set is used.
+    pub fn new(nfc: &NeoFoodClub, bets: Option<&Bets>) -> Self {
+        let max_ter = nfc.make_max_ter_bets();
+        let max_ter_binaries = sorted_binaries(&max_ter);
+        let bets = bets.cloned().unwrap_or(max_ter);
+        let binaries = bets.get_binaries();
+
+        let current_bets = bets.rebuild(nfc, &binaries);
+
+        // one copy, with only its modifier swapped for each move
+        let mut moved_nfc = nfc.copy(Some(nfc.probability_model.clone()), None);
+
+        let points = nfc
+            .get_arenas()
+            .arenas
+            .iter()
+            .flat_map(|arena| arena.pirates.iter())
+            .flat_map(|pirate| {
+                ODDS_STEPS.iter().filter_map(move |step| {
+                    let new_odds = pirate.current_odds as i8 + step;
+                    (2..=13)
+                        .contains(&new_odds)
+                        .then_some((*pirate, new_odds as u8))
+                })
+            })
+            .map(|(pirate, new_odds)| {
+                let mut custom_odds = nfc.modifier.custom_odds.clone().unwrap_or_default();
+                custom_odds.insert(pirate.id, new_odds);
+
+                moved_nfc.with_modifier(Modifier {
+                    custom_odds: Some(custom_odds),
+                    ..nfc.modifier.clone()
+                });
+
+                let moved_bets = bets.rebuild(&moved_nfc, &binaries);
+
+                SensitivityPoint {
+                    pirate,
+                    new_odds,
+                    expected_return: moved_bets.expected_return(&moved_nfc),
+                    net_expected: moved_bets.net_expected(&moved_nfc),
+                    bust_probability: bust_probability(&moved_bets),
+                    changes_max_ter: sorted_binaries(&moved_nfc.make_max_ter_bets())
+                        != max_ter_binaries,
+                }
+            })
+            .collect();
+
+        Self {
+            expected_return: current_bets.expected_return(nfc),
+            net_expected: current_bets.net_expected(nfc),
+            bust_probability: bust_probability(&current_bets),
+            points,
+        }
+    }
+
+    /// The odds moves that would change the max TER set.
+    pub fn max_ter_changes(&self) -> impl Iterator<Item = &SensitivityPoint> {
+        self.points.iter().filter(|point| point.changes_max_ter)
+    }
+
+    /// Returns a table of every point, with the change in ER from the current odds.
+    pub fn table(&self) -> String {
+        let mut table = Table::new();
+
+        table.set_header(vec![
+            "Arena", "Pirate", "Odds", "ER", "ER Δ", "NE", "Bust", "Max TER",
+        ]);
+
+        for point in self.points.iter() {
+            table.add_row(vec![
+                (point.pirate.arena_id + 1).to_string(),
+                point.pirate.get_name().to_string(),
+                format!("{}:1 → {}:1", point.pirate.current_odds, point.new_odds),
+                format!("{:.3}:1", point.expected_return),
+                format!("{:+.3}", point.expected_return - self.expected_return),
+                format!("{:.2}", point.net_expected),
+                format!("{:.3}%", point.bust_probability * 100.0),
+                if point.changes_max_ter { "Changes" } else { "" }.to_string(),
+            ]);
+        }
+
+        for column in table.column_iter_mut().skip(2) {
+            column.set_cell_alignment(comfy_table::CellAlignment::Center);
+        }
+
+        table.to_string()
+    }
+}
+
+fn bust_probability(bets: &Bets) -> f64 {
+    bets.odds.bust().map_or(0.0, |bust| bust.probability)
+}
+
+fn sorted_binaries(bets: &Bets) -> Vec<u32> {
+    bets.get_binaries().into_iter().sorted_unstable().collect()
+}
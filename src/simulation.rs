@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+
+use crate::{bets::Bets, chance::Chance, nfc::NeoFoodClub};
+
+/// The sampled results of playing a set of bets over many simulated rounds.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    /// Amount of rounds simulated.
+    pub rounds: u32,
+
+    /// The sampled distribution of units won, sorted by value.
+    pub units: Vec<Chance>,
+
+    /// The sampled distribution of neopoints won, sorted by value.
+    /// Empty if the bets have no bet amounts.
+    pub np: Vec<Chance>,
+}
+
+impl Simulation {
+    /// Simulates `rounds` rounds of the given bets, with winners sampled from the round's probabilities.
+    pub fn new<R: Rng + ?Sized>(nfc: &NeoFoodClub, bets: &Bets, rounds: u32, rng: &mut R) -> Self {
+        let mut units_counts: BTreeMap<u32, u32> = BTreeMap::new();
+        let mut np_counts: BTreeMap<u32, u32> = BTreeMap::new();
+
+        for _ in 0..rounds {
+            let outcome = nfc.evaluate_outcome(bets, nfc.sample_winners(rng));
+
+            *units_counts.entry(outcome.units).or_insert(0) += 1;
+            if let Some(np) = outcome.np {
+                *np_counts.entry(np).or_insert(0) += 1;
+            }
+        }
+
+        Self {
+            rounds,
+            units: chances_from_counts(units_counts, rounds),
+            np: chances_from_counts(np_counts, rounds),
+        }
+    }
+
+    /// The mean amount of units won per round.
+    pub fn mean_units(&self) -> f64 {
+        self.units
+            .iter()
+            .map(|c| c.value as f64 * c.probability)
+            .sum()
+    }
+
+    /// The mean amount of neopoints won per round.
+    pub fn mean_np(&self) -> f64 {
+        self.np.iter().map(|c| c.value as f64 * c.probability).sum()
+    }
+
+    /// The share of rounds where nothing was won.
+    pub fn bust_rate(&self) -> f64 {
+        self.units
+            .first()
+            .filter(|c| c.value == 0)
+            .map_or(0.0, |c| c.probability)
+    }
+}
+
+fn chances_from_counts(counts: BTreeMap<u32, u32>, total: u32) -> Vec<Chance> {
+    let mut cumulative: f64 = 0.0;
+    let mut tail: f64 = 1.0;
+    let mut chances: Vec<Chance> = Vec::with_capacity(counts.len());
+    for (value, count) in counts.into_iter() {
+        let probability = count as f64 / total as f64;
+        cumulative += probability;
+        chances.push(Chance {
+            value,
+            probability,
+            cumulative,
+            tail,
+        });
+
+        tail -= probability;
+    }
+    chances
+}
@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+use crate::{oddschange::OddsChange, round_data::RoundData};
+
+/// The odds of every pirate at one point in the round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OddsSnapshot {
+    /// When these odds took effect. None for the opening odds.
+    pub timestamp: Option<DateTime<Utc>>,
+
+    /// The odds of every pirate, in the same layout as `currentOdds`.
+    pub odds: [[u8; 5]; 5],
+}
+
+/// The odds of a round over time, rebuilt from its opening odds and changes.
+#[derive(Debug, Clone)]
+pub struct OddsTimeline {
+    pirates: [[u8; 4]; 5],
+    opening_odds: [[u8; 5]; 5],
+
+    /// The changes, paired with their parsed timestamps, sorted by time.
+    changes: Vec<(DateTime<Utc>, OddsChange)>,
+}
+
+impl OddsTimeline {
+    pub fn new(round_data: &RoundData) -> Self {
+        let mut changes: Vec<(DateTime<Utc>, OddsChange)> = round_data
+            .changes
+            .iter()
+            .flatten()
+            .map(|change| (change.timestamp_utc(), change.clone()))
+            .collect();
+
+        // stable, so changes sharing a timestamp keep their order
+        changes.sort_by_key(|(timestamp, _)| *timestamp);
+
+        Self {
+            pirates: round_data.pirates,
+            opening_odds: round_data.openingOdds,
+            changes,
+        }
+    }
+
+    /// Returns the opening odds.
+    pub fn opening_odds(&self) -> [[u8; 5]; 5] {
+        self.opening_odds
+    }
+
+    /// Returns the odds after every change.
+    pub fn closing_odds(&self) -> [[u8; 5]; 5] {
+        self.snapshots()
+            .last()
+            .map_or(self.opening_odds, |snapshot| snapshot.odds)
+    }
+
+    /// Returns the odds as they were at the given time, in UTC.
+    /// Changes made at exactly that time are included.
+    pub fn odds_at(&self, timestamp: DateTime<Utc>) -> [[u8; 5]; 5] {
+        let mut odds = self.opening_odds;
+        for change in self.changes_until(timestamp) {
+            odds[change.arena_index()][change.pirate_index()] = change.new;
+        }
+        odds
+    }
+
+    /// Returns the odds as they were at the given time, in NST.
+    /// Changes made at exactly that time are included.
+    pub fn odds_at_nst(&self, timestamp: DateTime<Tz>) -> [[u8; 5]; 5] {
+        self.odds_at(timestamp.with_timezone(&Utc))
+    }
+
+    /// Returns every change, sorted by time.
+    pub fn changes(&self) -> impl Iterator<Item = &OddsChange> {
+        self.changes.iter().map(|(_, change)| change)
+    }
+
+    /// Returns the changes made up to and including the given time, in UTC.
+    pub fn changes_until(&self, timestamp: DateTime<Utc>) -> impl Iterator<Item = &OddsChange> {
+        self.changes
+            .iter()
+            .take_while(move |(t, _)| *t <= timestamp)
+            .map(|(_, change)| change)
+    }
+
+    /// Returns the odds at the start of the round, and after each moment the odds changed.
+    /// Changes sharing a timestamp make up a single snapshot.
+    pub fn snapshots(&self) -> Vec<OddsSnapshot> {
+        let mut snapshots = vec![OddsSnapshot {
+            timestamp: None,
+            odds: self.opening_odds,
+        }];
+
+        let mut odds = self.opening_odds;
+        for (index, (timestamp, change)) in self.changes.iter().enumerate() {
+            odds[change.arena_index()][change.pirate_index()] = change.new;
+
+            let is_last_at_time = self
+                .changes
+                .get(index + 1)
+                .is_none_or(|(next, _)| next != timestamp);
+
+            if is_last_at_time {
+                snapshots.push(OddsSnapshot {
+                    timestamp: Some(*timestamp),
+                    odds,
+                });
+            }
+        }
+
+        snapshots
+    }
+
+    /// Returns the changes of the pirate at the given arena and pirate index, sorted by time.
+    /// The pirate index is 1-4, like in `currentOdds`.
+    pub fn changes_for(&self, arena_index: usize, pirate_index: usize) -> Vec<&OddsChange> {
+        self.changes()
+            .filter(|change| {
+                change.arena_index() == arena_index && change.pirate_index() == pirate_index
+            })
+            .collect()
+    }
+
+    /// Returns the changes of the pirate with the given ID, sorted by time.
+    /// Returns an empty vector if the pirate isn't in this round.
+    pub fn changes_for_pirate_id(&self, pirate_id: u8) -> Vec<&OddsChange> {
+        self.pirates
+            .iter()
+            .enumerate()
+            .find_map(|(arena_index, arena)| {
+                arena
+                    .iter()
+                    .position(|&id| id == pirate_id)
+                    .map(|pirate_index| self.changes_for(arena_index, pirate_index + 1))
+            })
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,233 @@
+use chrono::{DateTime, Utc};
+use comfy_table::Table;
+
+use crate::{bets::Bets, nfc::NeoFoodClub, utils::convert_from_utc_to_nst};
+
+/// A strategy's bets as they would have been made at one point in the round.
+#[derive(Debug, Clone)]
+pub struct TimingPoint {
+    /// When the odds took effect. None for the opening odds.
+    pub timestamp: Option<DateTime<Utc>>,
+
+    /// The bets the strategy would have made at this point.
+    pub bets: Bets,
+
+    /// The expected return of the bets.
+    pub expected_return: f64,
+
+    /// The net expected value of the bets, 0 if there is no bet amount.
+    pub net_expected: f64,
+
+    /// Whether or not the bets differ from the previous point's, ignoring order.
+    pub composition_changed: bool,
+}
+
+/// A strategy's bets recomputed at every odds snapshot of the round,
+/// to see when during the round they were worth the most.
+#[derive(Debug, Clone)]
+pub struct BetTiming {
+    /// One point per odds snapshot, in order of time.
+    pub points: Vec<TimingPoint>,
+
+    /// Whether or not the points are ranked by NE, rather than ER.
+    uses_net_expected: bool,
+}
+
+impl BetTiming {
+    pub fn new<F>(nfc: &NeoFoodClub, strategy: F) -> Self
+    where
+        F: Fn(&NeoFoodClub) -> Bets,
+    {
+        let mut points: Vec<TimingPoint> = Vec::new();
+        let mut previous_binaries: Option<Vec<u32>> = None;
+
+        for snapshot in nfc.odds_timeline().snapshots() {
+            let snapshot_nfc = nfc.at_snapshot(&snapshot);
+            let bets = strategy(&snapshot_nfc);
+
+            let mut binaries = bets.get_binaries();
+            binaries.sort_unstable();
+
+            let composition_changed = previous_binaries
+                .as_ref()
+                .is_some_and(|previous| *previous != binaries);
+            previous_binaries = Some(binaries);
+
+            points.push(TimingPoint {
+                timestamp: snapshot.timestamp,
+                expected_return: bets.expected_return(&snapshot_nfc),
+                net_expected: bets.net_expected(&snapshot_nfc),
+                bets,
+                composition_changed,
+            });
+        }
+
+        Self {
+            points,
+            uses_net_expected: nfc.bet_amount.is_some(),
+        }
+    }
+
+    /// The point where the bets were worth the most.
+    /// Ranked by NE if there's a bet amount, otherwise by ER.
+    /// Ties go to the earliest point.
+    pub fn best(&self) -> &TimingPoint {
+        self.points
+            .iter()
+            .rev()
+            .max_by(|a, b| self.value(a).total_cmp(&self.value(b)))
+            .expect("There is always at least the opening snapshot.")
+    }
+
+    /// The points where the strategy's bets changed composition.
+    pub fn composition_changes(&self) -> impl Iterator<Item = &TimingPoint> {
+        self.points.iter().filter(|point| point.composition_changed)
+    }
+
+    /// Returns a table of every point, in NST, marking the best one
+    /// and the ones where the bets changed composition.
+    pub fn table(&self) -> String {
+        let mut table = Table::new();
+
+        table.set_header(vec!["Time (NST)", "ER", "NE", "Changed", "Best"]);
+
+        let best = self.best();
+
+        for point in self.points.iter() {
+            table.add_row(vec![
+                format_timestamp(point.timestamp),
+                format!("{:.3}:1", point.expected_return),
+                format!("{:.2}", point.net_expected),
+                if point.composition_changed { "✓" } else { "" }.to_string(),
+                if std::ptr::eq(point, best) { "✓" } else { "" }.to_string(),
+            ]);
+        }
+
+        for column in table.column_iter_mut().skip(1) {
+            column.set_cell_alignment(comfy_table::CellAlignment::Center);
+        }
+
+        table.to_string()
+    }
+
+    fn value(&self, point: &TimingPoint) -> f64 {
+        if self.uses_net_expected {
+            point.net_expected
+        } else {
+            point.expected_return
+        }
+    }
+}
+
+/// A fixed set of bets' value at one point in the round.
+#[derive(Debug, Clone)]
+pub struct HistoryPoint {
+    /// When the odds took effect. None for the opening odds.
+    pub timestamp: Option<DateTime<Utc>>,
+
+    /// The expected return of the bets.
+    pub expected_return: f64,
+
+    /// The net expected value of the bets, 0 if they have no bet amounts.
+    pub net_expected: f64,
+
+    /// The probability of the bets busting.
+    pub bust_probability: f64,
+}
+
+/// A fixed set of bets re-evaluated at every odds snapshot of the round,
+/// to see whether it drifted into or out of favor.
+#[derive(Debug, Clone)]
+pub struct BetsHistory {
+    /// One point per odds snapshot, in order of time.
+    pub points: Vec<HistoryPoint>,
+}
+
+impl BetsHistory {
+    /// Re-evaluates the given bets at each snapshot, see `Bets::rebuild`.
+    pub fn new(nfc: &NeoFoodClub, bets: &Bets) -> Self {
+        let binaries = bets.get_binaries();
+
+        let points = nfc
+            .odds_timeline()
+            .snapshots()
+            .iter()
+            .map(|snapshot| {
+                let snapshot_nfc = nfc.at_snapshot(snapshot);
+
+                let snapshot_bets = bets.rebuild(&snapshot_nfc, &binaries);
+
+                HistoryPoint {
+                    timestamp: snapshot.timestamp,
+                    expected_return: snapshot_bets.expected_return(&snapshot_nfc),
+                    net_expected: snapshot_bets.net_expected(&snapshot_nfc),
+                    bust_probability: snapshot_bets
+                        .odds
+                        .bust()
+                        .map_or(0.0, |bust| bust.probability),
+                }
+            })
+            .collect();
+
+        Self { points }
+    }
+
+    /// Same as `new`, but for the bets in the given bets hash.
+    pub fn from_hash(nfc: &NeoFoodClub, hash: &str) -> Result<Self, String> {
+        Ok(Self::new(nfc, &Bets::from_hash(nfc, hash)?))
+    }
+
+    /// How much the expected return changed from the opening odds to the latest odds.
+    pub fn drift(&self) -> f64 {
+        let first = self
+            .points
+            .first()
+            .expect("There is always at least the opening snapshot.");
+        let last = self
+            .points
+            .last()
+            .expect("There is always at least the opening snapshot.");
+
+        last.expected_return - first.expected_return
+    }
+
+    /// The point where the bets had their highest expected return.
+    /// Ties go to the earliest point.
+    pub fn best(&self) -> &HistoryPoint {
+        self.points
+            .iter()
+            .rev()
+            .max_by(|a, b| a.expected_return.total_cmp(&b.expected_return))
+            .expect("There is always at least the opening snapshot.")
+    }
+
+    /// Returns a table of every point, in NST.
+    pub fn table(&self) -> String {
+        let mut table = Table::new();
+
+        table.set_header(vec!["Time (NST)", "ER", "NE", "Bust"]);
+
+        for point in self.points.iter() {
+            table.add_row(vec![
+                format_timestamp(point.timestamp),
+                format!("{:.3}:1", point.expected_return),
+                format!("{:.2}", point.net_expected),
+                format!("{:.3}%", point.bust_probability * 100.0),
+            ]);
+        }
+
+        for column in table.column_iter_mut().skip(1) {
+            column.set_cell_alignment(comfy_table::CellAlignment::Center);
+        }
+
+        table.to_string()
+    }
+}
+
+fn format_timestamp(timestamp: Option<DateTime<Utc>>) -> String {
+    timestamp.map_or("Opening".to_string(), |timestamp| {
+        convert_from_utc_to_nst(timestamp)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    })
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Duration, TimeDelta, TimeZone, Utc};
+use chrono::{DateTime, Duration, LocalResult, NaiveDateTime, TimeDelta, TimeZone, Utc};
 use chrono_tz::{OffsetComponents, Tz, US::Pacific};
 use std::cmp::Ordering;
 use std::sync::OnceLock;
@@ -55,3 +55,27 @@ pub fn timestamp_to_utc(timestamp: &str) -> DateTime<Utc> {
 pub fn convert_from_utc_to_nst(utc: DateTime<Utc>) -> DateTime<Tz> {
     Pacific.from_utc_datetime(&utc.naive_utc())
 }
+
+/// Returns when a round closes, at the same NST wall-clock time a day after it starts.
+/// Rounds spanning a DST change are 23 or 25 hours long.
+pub fn close_from_start(start: DateTime<Utc>) -> DateTime<Utc> {
+    let day_after = convert_from_utc_to_nst(start).naive_local() + Duration::try_days(1).unwrap();
+
+    nst_to_utc(day_after)
+}
+
+/// Converts a wall-clock time in NST to UTC.
+/// Ambiguous times (when clocks fall back) resolve to the earlier one,
+/// and nonexistent times (when clocks spring forward) are shifted forward by an hour.
+pub fn nst_to_utc(nst: NaiveDateTime) -> DateTime<Utc> {
+    let local = match Pacific.from_local_datetime(&nst) {
+        LocalResult::Single(local) => local,
+        LocalResult::Ambiguous(earliest, _) => earliest,
+        LocalResult::None => Pacific
+            .from_local_datetime(&(nst + TimeDelta::try_hours(1).unwrap()))
+            .earliest()
+            .expect("Shifting by an hour always leaves the DST gap."),
+    };
+
+    local.with_timezone(&Utc)
+}
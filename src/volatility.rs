@@ -0,0 +1,82 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::oddschange::OddsChange;
+
+/// Statistics on how the odds moved, over a round, an arena or a single pirate.
+#[derive(Debug, Clone)]
+pub struct OddsVolatility {
+    /// Amount of changes.
+    pub changes: usize,
+
+    /// Amount of changes where the odds went up.
+    pub ups: usize,
+
+    /// Amount of changes where the odds went down.
+    pub downs: usize,
+
+    /// The sum of every change's difference.
+    /// For a single pirate, this is its latest odds minus its opening odds.
+    pub net_drift: i32,
+
+    /// The sum of every change's absolute difference.
+    pub total_movement: u32,
+
+    /// The change with the largest absolute difference. The earliest one wins ties.
+    pub largest_swing: Option<OddsChange>,
+
+    /// When the last change happened.
+    pub last_change: Option<DateTime<Utc>>,
+
+    /// How long before the round closed the last change happened.
+    /// None if there are no changes or the round's start time is unknown.
+    pub last_change_before_close: Option<TimeDelta>,
+}
+
+impl OddsVolatility {
+    /// Computes the statistics for the given changes, which must be sorted by time.
+    pub fn new<'a>(
+        changes: impl IntoIterator<Item = &'a OddsChange>,
+        close: Option<DateTime<Utc>>,
+    ) -> Self {
+        let mut volatility = Self {
+            changes: 0,
+            ups: 0,
+            downs: 0,
+            net_drift: 0,
+            total_movement: 0,
+            largest_swing: None,
+            last_change: None,
+            last_change_before_close: None,
+        };
+
+        for change in changes {
+            let difference = change.difference() as i32;
+
+            volatility.changes += 1;
+            if difference > 0 {
+                volatility.ups += 1;
+            } else if difference < 0 {
+                volatility.downs += 1;
+            }
+            volatility.net_drift += difference;
+            volatility.total_movement += difference.unsigned_abs();
+
+            let is_largest = volatility
+                .largest_swing
+                .as_ref()
+                .is_none_or(|largest| difference.abs() > (largest.difference() as i32).abs());
+            if is_largest {
+                volatility.largest_swing = Some(change.clone());
+            }
+
+            volatility.last_change = Some(change.timestamp_utc());
+        }
+
+        volatility.last_change_before_close = volatility
+            .last_change
+            .zip(close)
+            .map(|(last_change, close)| close - last_change);
+
+        volatility
+    }
+}
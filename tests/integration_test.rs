@@ -5,7 +5,7 @@ use test::Bencher;
 
 use neofoodclub::math::{self, BET_AMOUNT_MAX, BET_AMOUNT_MIN};
 use neofoodclub::modifier::{Modifier, ModifierFlags};
-use neofoodclub::nfc::{NeoFoodClub, ProbabilityModel};
+use neofoodclub::nfc::{BetWeighting, NeoFoodClub, ProbabilityModel};
 
 // Round 8765
 const ROUND_DATA_JSON: &str = r#"
@@ -54,13 +54,20 @@ mod tests {
 
     use std::collections::HashMap;
 
-    use chrono::{DateTime, NaiveTime, TimeDelta};
+    use chrono::{DateTime, NaiveTime, TimeDelta, Utc};
+    use itertools::Itertools;
     use neofoodclub::{
-        bets::BetAmounts,
+        bankroll::{BankrollSimulation, BetAmountPolicy},
+        bets::{BetAmounts, Bets},
+        dominance::{self, DominatedBet},
         math::{make_round_dicts, pirate_binary},
-        modifier::Modifier,
+        modifier::{CustomTime, Modifier, ProbabilityOverride},
+        oddschange::OddsChange,
         pirates::PartialPirateThings,
+        round_data::RoundData,
+        timing::BetsHistory,
     };
+    use rand::{rngs::StdRng, SeedableRng};
     use serde::Deserialize;
 
     use super::*;
@@ -602,6 +609,160 @@ mod tests {
         assert!(bets.is_gambit());
     }
 
+    #[test]
+    fn test_make_weighted_crazy_bets() {
+        let nfc = make_test_nfc();
+
+        for weighting in [
+            BetWeighting::Probability,
+            BetWeighting::ExpectedReturn,
+            BetWeighting::NetExpected,
+        ] {
+            let bets = nfc.make_weighted_crazy_bets(weighting, 1.0);
+
+            assert!(bets.is_crazy());
+            assert_eq!(bets.len(), nfc.max_amount_of_bets());
+            assert_eq!(
+                bets.get_binaries().iter().unique().count(),
+                nfc.max_amount_of_bets()
+            );
+        }
+    }
+
+    #[test]
+    fn test_make_weighted_crazy_bets_greedy() {
+        let nfc = make_test_nfc();
+        let data = nfc.round_dict_data();
+
+        let bets = nfc.make_weighted_crazy_bets(BetWeighting::Probability, 0.0);
+
+        let mut full_probs: Vec<f64> = (0..3124)
+            .filter(|&i| data.bins[i].count_ones() == 5)
+            .map(|i| data.probs[i])
+            .collect();
+        full_probs.sort_by(|a, b| b.total_cmp(a));
+
+        let bets_probs: Vec<f64> = bets.array_indices.iter().map(|&i| data.probs[i]).collect();
+
+        assert_eq!(bets_probs, full_probs[..10]);
+
+        // greedy is deterministic
+        assert_eq!(
+            bets.get_binaries(),
+            nfc.make_weighted_crazy_bets(BetWeighting::Probability, 0.0)
+                .get_binaries()
+        );
+    }
+
+    #[test]
+    fn test_make_weighted_crazy_bets_uniform() {
+        let nfc = make_test_nfc();
+        let bets = nfc.make_weighted_crazy_bets(BetWeighting::ExpectedReturn, f64::INFINITY);
+
+        assert!(bets.is_crazy());
+        assert_eq!(bets.len(), nfc.max_amount_of_bets());
+    }
+
+    #[test]
+    fn test_make_weighted_gambit_bets() {
+        let nfc = make_test_nfc();
+        let bets = nfc.make_weighted_gambit_bets(BetWeighting::ExpectedReturn, 1.0);
+
+        assert!(bets.is_gambit());
+    }
+
+    #[test]
+    fn test_make_weighted_gambit_bets_greedy() {
+        let nfc = make_test_nfc();
+        let bets = nfc.make_weighted_gambit_bets(BetWeighting::NetExpected, 0.0);
+
+        assert_eq!(
+            bets.get_binaries(),
+            nfc.make_best_gambit_bets().get_binaries()
+        );
+    }
+
+    #[test]
+    fn test_make_weighted_gambit_bets_proportional() {
+        let nfc = make_test_nfc();
+        let data = nfc.round_dict_data();
+        let mut rng = StdRng::seed_from_u64(26);
+
+        let full_indices = (0..3124)
+            .filter(|&i| data.bins[i].count_ones() == 5)
+            .collect_vec();
+
+        for (weighting, values) in [
+            (BetWeighting::Probability, &data.probs),
+            (BetWeighting::ExpectedReturn, &data.ers),
+        ] {
+            let total: f64 = full_indices.iter().map(|&i| values[i]).sum();
+            let top = *full_indices
+                .iter()
+                .max_by(|&&a, &&b| values[a].total_cmp(&values[b]))
+                .unwrap();
+            let expected = values[top] / total;
+
+            let draws = 1000;
+            let hits = (0..draws)
+                .filter(|_| {
+                    nfc.make_weighted_gambit_bets_with_rng(weighting, 1.0, &mut rng)
+                        .get_binaries()
+                        .contains(&data.bins[top])
+                })
+                .count();
+
+            // within four standard deviations of picking in proportion to the weights
+            let frequency = hits as f64 / draws as f64;
+            let deviation = (expected * (1.0 - expected) / draws as f64).sqrt();
+            assert!(
+                (frequency - expected).abs() < 4.0 * deviation,
+                "{weighting:?}: picked the top bet {frequency}, expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_make_weighted_crazy_bets_unclamped_bet_amount() {
+        let mut nfc = make_test_nfc();
+        nfc.bet_amount = Some(0);
+
+        let bets = nfc.make_weighted_crazy_bets_with_rng(
+            BetWeighting::NetExpected,
+            1.0,
+            &mut StdRng::seed_from_u64(26),
+        );
+        assert_eq!(bets.len(), 10);
+    }
+
+    #[test]
+    fn test_make_weighted_crazy_bets_low_temperature_is_greedy() {
+        let nfc = make_test_nfc();
+        let greedy = nfc.make_weighted_crazy_bets(BetWeighting::ExpectedReturn, 0.0);
+        let mut rng = StdRng::seed_from_u64(26);
+
+        // bets with the same ER can swap places, so compare the ERs
+        for _ in 0..20 {
+            let bets =
+                nfc.make_weighted_crazy_bets_with_rng(BetWeighting::ExpectedReturn, 1e-6, &mut rng);
+            assert_eq!(
+                bets.expected_return_list(&nfc),
+                greedy.expected_return_list(&nfc)
+            );
+        }
+
+        // the same seed makes the same bets
+        let sample = |seed| {
+            nfc.make_weighted_crazy_bets_with_rng(
+                BetWeighting::NetExpected,
+                1.0,
+                &mut StdRng::seed_from_u64(seed),
+            )
+            .get_binaries()
+        };
+        assert_eq!(sample(1), sample(1));
+    }
+
     #[test]
     fn test_make_random_bets() {
         let nfc = make_test_nfc();
@@ -1020,408 +1181,2346 @@ mod tests {
     }
 
     #[test]
-    fn test_logit() {
-        let nfc = make_test_nfc_logit();
-        let bets = nfc.make_best_gambit_bets();
-
-        assert!(bets.is_gambit());
-    }
-
-    #[test]
-    fn test_last_change_with_timezones() {
+    fn test_odds_timeline_snapshots() {
         let nfc = make_test_nfc();
+        let timeline = nfc.odds_timeline();
 
-        assert_eq!(
-            nfc.last_change_nst().unwrap().to_string(),
-            "2023-05-06 12:21:01 PDT"
-        );
+        let snapshots = timeline.snapshots();
 
-        assert_eq!(
-            nfc.last_change_utc().unwrap().to_string(),
-            "2023-05-06 19:21:01 UTC"
-        );
+        // 15 changes, three of which share a timestamp
+        assert_eq!(snapshots.len(), 14);
+        assert_eq!(snapshots[0].timestamp, None);
+        assert_eq!(snapshots[0].odds, nfc.opening_odds());
+        assert_eq!(snapshots.last().unwrap().odds, *nfc.current_odds());
+        assert_eq!(snapshots.last().unwrap().timestamp, nfc.last_change_utc());
+        assert_eq!(timeline.closing_odds(), *nfc.current_odds());
+        assert!(snapshots
+            .windows(2)
+            .skip(1)
+            .all(|pair| pair[0].timestamp < pair[1].timestamp));
     }
 
     #[test]
-    fn test_timestamp_with_timezones() {
+    fn test_odds_timeline_odds_at() {
         let nfc = make_test_nfc();
+        let timeline = nfc.odds_timeline();
 
         assert_eq!(
-            nfc.timestamp_nst().unwrap().to_string(),
-            "2023-05-06 16:14:20 PDT"
+            timeline.odds_at(nfc.start_utc().unwrap()),
+            nfc.opening_odds()
         );
 
+        // changes at exactly the given time are included
+        let first_change = DateTime::parse_from_rfc3339("2023-05-06T00:17:30+00:00")
+            .unwrap()
+            .to_utc();
+        assert_eq!(timeline.odds_at(first_change)[1][3], 7);
         assert_eq!(
-            nfc.timestamp_utc().unwrap().to_string(),
-            "2023-05-06 23:14:20 UTC"
+            timeline.odds_at(first_change - TimeDelta::seconds(1))[1][3],
+            5
         );
+
+        let nst = first_change.with_timezone(&chrono_tz::US::Pacific);
+        assert_eq!(timeline.odds_at_nst(nst), timeline.odds_at(first_change));
+        assert_eq!(timeline.changes_until(first_change).count(), 1);
     }
 
     #[test]
-    fn test_start_with_timezones() {
+    fn test_odds_timeline_changes_for_pirate() {
         let nfc = make_test_nfc();
+        let timeline = nfc.odds_timeline();
 
+        // pirate 3 is the 4th pirate in Shipwreck
+        let changes = timeline.changes_for_pirate_id(3);
+        assert_eq!(changes.len(), 3);
         assert_eq!(
-            nfc.start_nst().unwrap().to_string(),
-            "2023-05-05 16:14:57 PDT"
+            changes.iter().map(|c| (c.old, c.new)).collect::<Vec<_>>(),
+            vec![(4, 3), (3, 4), (4, 3)]
         );
+        assert_eq!(timeline.changes_for(0, 4).len(), 3);
 
-        assert_eq!(
-            nfc.start_utc().unwrap().to_string(),
-            "2023-05-05 23:14:57 UTC"
-        );
+        assert!(timeline.changes_for_pirate_id(21).is_empty());
     }
 
     #[test]
-    fn test_timestamp() {
-        let nfc = make_test_nfc();
+    fn test_odds_timeline_no_changes() {
+        let nfc = make_test_nfc_from_url();
+        let timeline = nfc.odds_timeline();
 
-        assert_eq!(
-            nfc.timestamp().as_ref().unwrap(),
-            "2023-05-06T23:14:20+00:00"
-        );
+        assert_eq!(timeline.snapshots().len(), 1);
+        assert_eq!(timeline.closing_odds(), nfc.opening_odds());
     }
 
     #[test]
-    fn test_last_change() {
+    fn test_at_snapshot() {
         let nfc = make_test_nfc();
+        let snapshots = nfc.odds_timeline().snapshots();
 
-        assert_eq!(
-            nfc.last_change().as_ref().unwrap(),
-            "2023-05-06T19:21:01+00:00"
-        );
+        let opening = nfc.at_snapshot(&snapshots[0]);
+        assert_eq!(*opening.current_odds(), nfc.opening_odds());
+        assert!(opening.changes().is_none());
+
+        let closing = nfc.at_snapshot(snapshots.last().unwrap());
+        assert_eq!(closing.round_dict_data().odds, nfc.round_dict_data().odds);
+        assert_eq!(closing.changes().as_ref().unwrap().len(), 15);
     }
 
     #[test]
-    fn test_opening_odds() {
+    fn test_bet_timing_max_ter() {
         let nfc = make_test_nfc();
 
-        assert_eq!(
-            nfc.opening_odds(),
-            [
-                [1, 11, 3, 2, 4],
-                [1, 13, 2, 5, 13],
-                [1, 13, 2, 5, 2],
-                [1, 2, 8, 5, 5],
-                [1, 13, 3, 2, 4]
-            ]
-        );
-    }
+        let timing = nfc.bet_timing(|nfc| nfc.make_max_ter_bets());
 
-    #[test]
-    fn test_pirates() {
-        let nfc = make_test_nfc();
+        assert_eq!(timing.points.len(), 14);
+        assert!(!timing.points[0].composition_changed);
 
+        let opening_nfc = make_test_nfc_with_modifier(
+            Modifier::new(ModifierFlags::OPENING_ODDS.bits(), None, None).unwrap(),
+        );
         assert_eq!(
-            nfc.pirates(),
-            [
-                [6, 11, 4, 3],
-                [14, 15, 2, 9],
-                [10, 16, 18, 20],
-                [1, 12, 13, 5],
-                [8, 19, 17, 7]
-            ]
+            timing.points[0].bets.get_binaries(),
+            opening_nfc.make_max_ter_bets().get_binaries()
         );
+
+        let last = timing.points.last().unwrap();
+        let max_ter = nfc.make_max_ter_bets();
+        assert_eq!(last.bets.get_binaries(), max_ter.get_binaries());
+        assert!((last.net_expected - max_ter.net_expected(&nfc)).abs() < 1e-6);
+
+        let best = timing.best();
+        assert!(timing
+            .points
+            .iter()
+            .all(|point| point.net_expected <= best.net_expected));
+
+        for pair in timing.points.windows(2) {
+            let mut before = pair[0].bets.get_binaries();
+            let mut after = pair[1].bets.get_binaries();
+            before.sort_unstable();
+            after.sort_unstable();
+            assert_eq!(pair[1].composition_changed, before != after);
+        }
+        assert!(timing.composition_changes().all(|p| p.timestamp.is_some()));
+
+        let table = timing.table();
+        assert_eq!(table.lines().count(), 3 + 14 * 2);
+        assert!(table.contains("Opening"));
     }
 
     #[test]
-    fn test_modified() {
+    fn test_bets_history() {
         let nfc = make_test_nfc();
+        let bets = nfc.make_max_ter_bets();
 
-        let mut custom_odds = HashMap::<u8, u8>::new();
-        custom_odds.insert(1, 13);
+        let history = nfc.bets_history(&bets);
 
-        let modifier = Modifier::new(
-            ModifierFlags::EMPTY.bits(),
-            Some(custom_odds.clone()),
-            NaiveTime::from_hms_opt(12, 0, 0),
-        )
-        .unwrap();
+        assert_eq!(history.points.len(), 14);
 
-        let modified_nfc = nfc.copy(None, Some(modifier));
+        let last = history.points.last().unwrap();
+        assert!((last.expected_return - bets.expected_return(&nfc)).abs() < 1e-9);
+        assert!((last.net_expected - bets.net_expected(&nfc)).abs() < 1e-6);
+        let bust_probability = bets.odds.bust().map_or(0.0, |b| b.probability);
+        assert!((last.bust_probability - bust_probability).abs() < 1e-12);
 
-        assert!(modified_nfc.modified());
+        let first = &history.points[0];
+        assert_eq!(first.timestamp, None);
+        assert!((history.drift() - (last.expected_return - first.expected_return)).abs() < 1e-12);
 
-        assert_ne!(modified_nfc.custom_odds(), *modified_nfc.current_odds());
+        let best = history.best();
+        assert!(history
+            .points
+            .iter()
+            .all(|p| p.expected_return <= best.expected_return));
 
-        assert_eq!(modified_nfc.modifier.custom_odds, Some(custom_odds));
+        assert!(history.table().contains("Opening"));
     }
 
     #[test]
-    fn test_to_json() {
+    fn test_bets_history_from_hash() {
         let nfc = make_test_nfc();
+        let bets = nfc.make_max_ter_bets();
 
-        let json = nfc.to_json();
+        let from_hash = BetsHistory::from_hash(&nfc, &bets.bets_hash()).unwrap();
+        let history = nfc.bets_history(&bets);
 
-        let new_nfc = NeoFoodClub::from_json(&json, None, None, None);
+        for (a, b) in from_hash.points.iter().zip(history.points.iter()) {
+            assert_eq!(a.timestamp, b.timestamp);
+            assert!((a.expected_return - b.expected_return).abs() < 1e-12);
+            // the hash carries no bet amounts
+            assert_eq!(a.net_expected, 0.0);
+        }
 
-        assert_eq!(new_nfc.round(), nfc.round());
-        assert!(new_nfc.modifier.is_empty());
+        assert!(BetsHistory::from_hash(&nfc, "!!!").is_err());
     }
 
     #[test]
-    fn test_modifier_copy() {
-        let mut custom_odds = HashMap::<u8, u8>::new();
-        custom_odds.insert(1, 13);
+    fn test_round_volatility() {
+        let nfc = make_test_nfc();
 
-        let modifier = Modifier::new(ModifierFlags::EMPTY.bits(), Some(custom_odds), None).unwrap();
+        let volatility = nfc.volatility();
 
-        let new_modifier = modifier.copy();
+        assert_eq!(volatility.changes, 15);
+        assert_eq!(volatility.ups, 9);
+        assert_eq!(volatility.downs, 6);
+        assert_eq!(volatility.net_drift, 5);
+        assert_eq!(volatility.total_movement, 17);
 
-        assert_eq!(modifier, new_modifier);
+        let largest_swing = volatility.largest_swing.unwrap();
+        assert_eq!((largest_swing.old, largest_swing.new), (5, 7));
+        assert_eq!(largest_swing.difference(), 2);
+
+        assert_eq!(volatility.last_change, nfc.last_change_utc());
+        assert_eq!(
+            volatility.last_change_before_close,
+            Some(TimeDelta::seconds(3 * 3600 + 53 * 60 + 56))
+        );
     }
 
     #[test]
-    fn test_odds_change_data() {
+    fn test_arena_and_pirate_volatility() {
         let nfc = make_test_nfc();
+        let arenas = nfc.get_arenas();
 
-        let changes = nfc.changes().as_ref().unwrap();
-        let odds_change = changes.first().unwrap();
+        let hidden = arenas.get_arena(3).unwrap().volatility(&nfc);
+        assert_eq!(hidden.changes, 5);
+        assert_eq!(hidden.ups, 4);
+        assert_eq!(hidden.downs, 1);
+        assert_eq!(hidden.net_drift, 4);
+
+        // pirate 3 went 4 -> 3 -> 4 -> 3
+        let pirate = arenas.get_pirate_by_id(3).unwrap();
+        let volatility = pirate.volatility(&nfc);
+        assert_eq!(volatility.changes, 3);
+        assert_eq!(volatility.ups, 1);
+        assert_eq!(volatility.downs, 2);
+        assert_eq!(
+            volatility.net_drift,
+            pirate.current_odds as i32 - pirate.opening_odds as i32
+        );
+        assert_eq!(volatility.total_movement, 3);
 
-        assert_eq!(odds_change.pirate(&nfc).id, 2);
-        assert_eq!(odds_change.arena(), "Lagoon");
+        let total: usize = arenas
+            .arenas
+            .iter()
+            .map(|arena| arena.volatility(&nfc).changes)
+            .sum();
+        assert_eq!(total, nfc.volatility().changes);
     }
 
     #[test]
-    fn test_make_url_all_data() {
-        let nfc = make_test_nfc();
+    fn test_volatility_no_changes() {
+        let nfc = make_test_nfc_from_url();
 
-        let bets = nfc.make_bustproof_bets().unwrap();
+        let volatility = nfc.volatility();
 
-        let url = nfc.make_url(Some(&bets), true, true);
+        assert_eq!(volatility.changes, 0);
+        assert_eq!(volatility.net_drift, 0);
+        assert!(volatility.largest_swing.is_none());
+        assert!(volatility.last_change.is_none());
+        assert!(volatility.last_change_before_close.is_none());
+    }
 
-        assert!(url.contains("winners"));
-        assert!(url.contains("timestamp"));
+    fn round_data_at(timestamp: &str) -> RoundData {
+        let mut round_data: RoundData = serde_json::from_str(ROUND_DATA_JSON).unwrap();
+        let timestamp = DateTime::parse_from_rfc3339(timestamp).unwrap().to_utc();
+        round_data.currentOdds = round_data.odds_at(timestamp).unwrap();
+        round_data.winners = None;
+
+        let changes: Vec<OddsChange> = round_data
+            .changes
+            .unwrap()
+            .into_iter()
+            .filter(|change| change.timestamp_utc() <= timestamp)
+            .collect();
+        round_data.lastChange = changes.last().map(|change| change.t.clone());
+        round_data.changes = (!changes.is_empty()).then_some(changes);
+        round_data
     }
 
     #[test]
-    fn test_make_url_all_data_no_bets() {
-        let nfc = make_test_nfc();
+    fn test_round_data_diff_matches_changes() {
+        let older = round_data_at("2023-05-06T00:20:00+00:00");
+        let newer = round_data_at("2023-05-06T00:21:43+00:00");
 
-        let url = nfc.make_url(None, true, false);
+        let timestamp = DateTime::parse_from_rfc3339("2023-05-06T00:21:43+00:00")
+            .unwrap()
+            .to_utc();
+        let diff = older.diff(&newer, timestamp).unwrap();
 
-        assert_eq!(url, "https://neofood.club/#round=8765");
+        let original: RoundData = serde_json::from_str(ROUND_DATA_JSON).unwrap();
+        let expected = &original.changes.unwrap()[1..4];
+
+        assert_eq!(
+            serde_json::to_string(&diff.changes).unwrap(),
+            serde_json::to_string(expected).unwrap()
+        );
+        assert!(diff.winners.is_none());
+        assert!(diff.food_mismatches.is_empty());
+        assert!(diff.lineup_mismatches.is_empty());
+        assert!(!diff.is_empty());
     }
 
     #[test]
-    fn test_make_all_max_ter_bets() {
-        let nfc = make_test_nfc();
+    fn test_round_data_diff_winners_and_mismatches() {
+        let older = round_data_at("2023-05-06T23:00:00+00:00");
+        let mut newer: RoundData = serde_json::from_str(ROUND_DATA_JSON).unwrap();
+        newer.pirates[4].swap(0, 1);
+        newer.foods.as_mut().unwrap()[2][0] = 40;
+        newer.currentOdds[4][1] = 2;
 
-        let bets = nfc.make_all_max_ter_bets();
+        let diff = older.diff(&newer, Utc::now()).unwrap();
 
-        assert_eq!(bets.len(), 3124);
+        assert_eq!(diff.winners, Some([3, 2, 3, 2, 2]));
+        assert_eq!(diff.food_mismatches, vec![2]);
+        assert_eq!(diff.lineup_mismatches, vec![4]);
+        // the odds change is in an arena whose lineup changed
+        assert!(diff.changes.is_empty());
+
+        assert!(older.diff(&older, Utc::now()).unwrap().is_empty());
     }
 
     #[test]
-    fn test_is_outdated_lock() {
-        let nfc = make_test_nfc();
+    fn test_round_data_diff_different_rounds() {
+        let older = round_data_at("2023-05-06T23:00:00+00:00");
+        let mut newer = older.clone();
+        newer.round += 1;
 
-        // our test data is from 2023-05-06
-        // this is probably always going to be true
-        assert!(nfc.is_outdated_lock());
+        assert!(older.diff(&newer, Utc::now()).is_err());
     }
 
     #[test]
-    fn test_bets_table() {
-        let nfc = make_test_nfc();
+    fn test_apply_snapshot_odds() {
+        let older = round_data_at("2023-05-06T00:20:00+00:00");
+        let newer = round_data_at("2023-05-06T00:21:43+00:00");
 
-        let bets = nfc.make_bustproof_bets().unwrap();
+        let mut nfc = NeoFoodClub::new(older, Some(BET_AMOUNT), None, None);
+        // warm the caches up
+        nfc.make_max_ter_bets();
+        let harpoon_odds = nfc.round_dict_data().odds[..4].to_vec();
 
-        let table = bets.table(&nfc);
+        let diff = nfc.apply_snapshot(&newer).unwrap();
+        assert_eq!(diff.changes.len(), 3);
 
+        let fresh = NeoFoodClub::new(newer.clone(), Some(BET_AMOUNT), None, None);
+
+        assert_eq!(nfc.round_dict_data().odds, fresh.round_dict_data().odds);
+        assert_eq!(nfc.round_dict_data().ers, fresh.round_dict_data().ers);
         assert_eq!(
-            table,
-            r#"
-+---+-----------+----------+----------+---------+---------+
-| # | Shipwreck | Lagoon   | Treasure | Hidden  | Harpoon |
-+=========================================================+
-| 1 |           | Sproggie |          |         |         |
-|---+-----------+----------+----------+---------+---------|
-| 2 |           | Fairfax  |          |         |         |
-|---+-----------+----------+----------+---------+---------|
-| 3 |           | Stuff    |          |         |         |
-|---+-----------+----------+----------+---------+---------|
-| 4 |           | Gooblah  |          | Dan     |         |
-|---+-----------+----------+----------+---------+---------|
-| 5 |           | Gooblah  |          | Stripey |         |
-|---+-----------+----------+----------+---------+---------|
-| 6 |           | Gooblah  |          | Ned     |         |
-|---+-----------+----------+----------+---------+---------|
-| 7 |           | Gooblah  |          | Edmund  |         |
-+---+-----------+----------+----------+---------+---------+
-"#
-            .trim()
-        )
+            nfc.round_dict_data().maxbets,
+            fresh.round_dict_data().maxbets
+        );
+        // rows that only bet on Harpoon were left alone
+        assert_eq!(nfc.round_dict_data().odds[..4], harpoon_odds[..]);
+
+        assert_eq!(
+            nfc.make_max_ter_bets().get_binaries(),
+            fresh.make_max_ter_bets().get_binaries()
+        );
+        assert_eq!(
+            nfc.get_arenas().get_arena(3).unwrap().pirates[1].current_odds,
+            10
+        );
+        assert_eq!(nfc.changes().as_ref().unwrap().len(), 4);
+        assert_eq!(nfc.last_change(), &newer.lastChange);
     }
 
     #[test]
-    fn test_bets_stats_table() {
-        let nfc = make_test_nfc();
-
-        let bets = nfc.make_bustproof_bets().unwrap();
+    fn test_apply_snapshot_keeps_intermediate_changes() {
+        let older = round_data_at("2023-05-06T01:00:00+00:00");
+        let newer = round_data_at("2023-05-06T07:30:00+00:00");
 
-        let table = bets.stats_table(&nfc);
+        let mut nfc = NeoFoodClub::new(older.clone(), Some(BET_AMOUNT), None, None);
+        nfc.apply_snapshot(&newer).unwrap();
 
+        // Lagoon's pirate went 4:1, 3:1, 4:1 then 3:1 in between, with its own timestamps
         assert_eq!(
-            table,
-            r#"
-+---+------+---------+---------+--------+---------+-----------+----------+----------+---------+---------+
-| # | Odds |    ER   |    NE   | MaxBet |   Hex   | Shipwreck |  Lagoon  | Treasure |  Hidden | Harpoon |
-+=======================================================================================================+
-| 1 |   7  | 1.283:1 | 1295.12 | 142858 | 0x02000 |           | Sproggie |          |         |         |
-|---+------+---------+---------+--------+---------+-----------+----------+----------+---------+---------|
-| 2 |  13  | 0.650:1 | -861.35 |  76924 | 0x08000 |           |  Fairfax |          |         |         |
-|---+------+---------+---------+--------+---------+-----------+----------+----------+---------+---------|
-| 3 |  13  | 0.650:1 | -861.35 |  76924 | 0x01000 |           |   Stuff  |          |         |         |
-|---+------+---------+---------+--------+---------+-----------+----------+----------+---------+---------|
-| 4 |   4  | 1.477:1 | 3817.04 | 250000 | 0x04080 |           |  Gooblah |          |   Dan   |         |
-|---+------+---------+---------+--------+---------+-----------+----------+----------+---------+---------|
-| 5 |  20  | 1.692:1 | 1107.41 |  50000 | 0x04040 |           |  Gooblah |          | Stripey |         |
-|---+------+---------+---------+--------+---------+-----------+----------+----------+---------+---------|
-| 6 |  12  | 1.577:1 | 1537.39 |  83334 | 0x04020 |           |  Gooblah |          |   Ned   |         |
-|---+------+---------+---------+--------+---------+-----------+----------+----------+---------+---------|
-| 7 |  12  | 1.577:1 | 1537.39 |  83334 | 0x04010 |           |  Gooblah |          |  Edmund |         |
-+---+------+---------+---------+--------+---------+-----------+----------+----------+---------+---------+"#
-            .trim()
-        )
+            serde_json::to_string(nfc.changes()).unwrap(),
+            serde_json::to_string(&newer.changes).unwrap()
+        );
+
+        // applying it again doesn't duplicate anything
+        nfc.apply_snapshot(&newer).unwrap();
+        assert_eq!(nfc.changes().as_ref().unwrap().len(), 8);
+
+        // without changes, only the net moves can be synthesized
+        let mut bare = newer.clone();
+        bare.changes = None;
+        let mut nfc = NeoFoodClub::new(older.clone(), Some(BET_AMOUNT), None, None);
+        let diff = nfc.apply_snapshot(&bare).unwrap();
+        assert_eq!(diff.changes.len(), 2);
+        assert_eq!(nfc.changes().as_ref().unwrap().len(), 6);
+        assert_eq!(nfc.custom_odds(), newer.currentOdds);
+
+        // the same goes for an empty list, so the log still ends at the current odds
+        bare.changes = Some(vec![]);
+        let mut nfc = NeoFoodClub::new(older, Some(BET_AMOUNT), None, None);
+        nfc.apply_snapshot(&bare).unwrap();
+        assert_eq!(nfc.changes().as_ref().unwrap().len(), 6);
+        assert_eq!(nfc.odds_timeline().odds_at(Utc::now()), newer.currentOdds);
     }
 
     #[test]
-    fn test_bets_stats_table_reverse_mer() {
-        let nfc = make_test_nfc_with_modifier(
-            Modifier::new(ModifierFlags::REVERSE.bits(), None, None).unwrap(),
-        );
+    fn test_apply_snapshot_with_custom_time() {
+        let instant = DateTime::parse_from_rfc3339("2023-05-06T00:21:43+00:00")
+            .unwrap()
+            .to_utc();
+        let modifier = Modifier::default().with_custom_time(Some(instant.into()));
+        let older = round_data_at("2023-05-06T00:20:00+00:00");
+        let newer: RoundData = serde_json::from_str(ROUND_DATA_JSON).unwrap();
 
-        let bets = nfc.make_max_ter_bets();
+        let mut nfc = NeoFoodClub::new(older, Some(BET_AMOUNT), None, Some(modifier.clone()));
+        nfc.apply_snapshot(&newer).unwrap();
 
-        let table = bets.stats_table(&nfc);
+        let fresh = NeoFoodClub::new(newer, Some(BET_AMOUNT), None, Some(modifier));
+        assert_eq!(nfc.custom_odds(), fresh.custom_odds());
+        assert_eq!(nfc.changes().as_ref().unwrap().len(), 4);
+
+        // the changes after the custom time are still there
+        assert_eq!(nfc.all_changes().as_ref().unwrap().len(), 15);
+        nfc.with_modifier(Modifier::default());
+        assert_eq!(nfc.changes().as_ref().unwrap().len(), 15);
+        assert_eq!(nfc.custom_odds(), make_test_nfc().custom_odds());
+    }
+
+    #[test]
+    fn test_apply_snapshot_winners() {
+        let older = round_data_at("2023-05-06T23:00:00+00:00");
+        let newer: RoundData = serde_json::from_str(ROUND_DATA_JSON).unwrap();
+
+        let mut nfc = NeoFoodClub::new(older, Some(BET_AMOUNT), None, None);
+        let bets = nfc.make_bustproof_bets().unwrap();
+        assert_eq!(nfc.get_win_units(&bets), 0);
+
+        let diff = nfc.apply_snapshot(&newer).unwrap();
+
+        assert!(diff.changes.is_empty());
+        assert_eq!(nfc.winners(), [3, 2, 3, 2, 2]);
+        assert_eq!(nfc.get_win_units(&bets), 20);
+        assert_eq!(nfc.changes().as_ref().unwrap().len(), 15);
+    }
+
+    #[test]
+    fn test_apply_snapshot_errors() {
+        let mut nfc = make_test_nfc();
+
+        let mut other_round: RoundData = serde_json::from_str(ROUND_DATA_JSON).unwrap();
+        other_round.round += 1;
+        assert!(nfc.apply_snapshot(&other_round).is_err());
+
+        let mut other_lineup: RoundData = serde_json::from_str(ROUND_DATA_JSON).unwrap();
+        other_lineup.pirates[0].swap(0, 1);
+        assert!(nfc.apply_snapshot(&other_lineup).is_err());
+
+        // bad data is an error, not a panic
+        let mut bad_odds: RoundData = serde_json::from_str(ROUND_DATA_JSON).unwrap();
+        bad_odds.currentOdds[0][1] = 14;
+        assert_eq!(
+            nfc.apply_snapshot(&bad_odds).unwrap_err(),
+            "Odds must be between 2 and 13."
+        );
+    }
+
+    #[test]
+    fn test_odds_change_new() {
+        let change = OddsChange::new("2023-05-06T00:17:30+00:00".to_string(), 7, 5, 1, 3);
+
+        assert_eq!(change.arena(), "Lagoon");
+        assert_eq!(change.pirate_index(), 3);
+        assert_eq!(change.difference(), 2);
+        assert_eq!(
+            serde_json::to_string(&change).unwrap(),
+            r#"{"t":"2023-05-06T00:17:30+00:00","new":7,"old":5,"arena":1,"pirate":3}"#
+        );
+    }
+
+    fn make_test_nfc_with_custom_time(custom_time: CustomTime) -> NeoFoodClub {
+        let modifier = Modifier::new(ModifierFlags::EMPTY.bits(), None, None)
+            .unwrap()
+            .with_custom_time(Some(custom_time));
+
+        make_test_nfc_with_modifier(modifier)
+    }
+
+    #[test]
+    fn test_modifier_custom_time_at() {
+        let instant = DateTime::parse_from_rfc3339("2023-05-06T00:21:43+00:00")
+            .unwrap()
+            .to_utc();
+
+        let nfc = make_test_nfc_with_custom_time(instant.into());
+        assert_eq!(nfc.changes().as_ref().unwrap().len(), 4);
+
+        // the same instant, in NST
+        let nst = instant.with_timezone(&chrono_tz::US::Pacific);
+        let nst_nfc = make_test_nfc_with_custom_time(nst.into());
+        assert_eq!(nst_nfc.custom_odds(), nfc.custom_odds());
+
+        // and as an NST time of day
+        let time_nfc = make_test_nfc_with_custom_time(nst.time().into());
+        assert_eq!(time_nfc.custom_odds(), nfc.custom_odds());
+    }
+
+    #[test]
+    fn test_modifier_custom_time_after_start() {
+        let nfc = make_test_nfc_with_custom_time(CustomTime::AfterStart(TimeDelta::minutes(70)));
+
+        assert_eq!(nfc.changes().as_ref().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_modifier_custom_time_before_close() {
+        let nfc = make_test_nfc_with_custom_time(CustomTime::BeforeClose(TimeDelta::hours(5)));
+
+        assert_eq!(nfc.changes().as_ref().unwrap().len(), 13);
+        assert_eq!(
+            nfc.custom_odds(),
+            nfc.odds_timeline()
+                .odds_at(nfc.close_utc().unwrap() - TimeDelta::hours(5))
+        );
+    }
+
+    fn make_test_nfc_with_custom_probabilities(
+        custom_probabilities: HashMap<u8, ProbabilityOverride>,
+    ) -> NeoFoodClub {
+        let modifier = Modifier::default()
+            .with_custom_probabilities(Some(custom_probabilities))
+            .unwrap();
+
+        make_test_nfc_with_modifier(modifier)
+    }
+
+    #[test]
+    fn test_modifier_custom_probabilities_pin() {
+        let base = make_test_nfc().probabilities();
+        // pirate 4 is the third pirate in the first arena
+        let nfc = make_test_nfc_with_custom_probabilities(HashMap::from([(
+            4,
+            ProbabilityOverride::Pin(0.6),
+        )]));
+        let probs = nfc.probabilities();
+
+        assert!((probs[0][3] - 0.6).abs() < 1e-12);
+        assert!((probs[0][1..].iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+        // the rest of the arena keeps its proportions
+        let others = base[0][1] + base[0][2] + base[0][4];
+        for pirate_index in [1, 2, 4] {
+            let expected = base[0][pirate_index] / others * 0.4;
+            assert!((probs[0][pirate_index] - expected).abs() < 1e-12);
+        }
+
+        assert_eq!(probs[1..], base[1..]);
+    }
+
+    #[test]
+    fn test_modifier_custom_probabilities_scale() {
+        let base = make_test_nfc().probabilities();
+        // pirate 14 is the first pirate in the second arena
+        let nfc = make_test_nfc_with_custom_probabilities(HashMap::from([(
+            14,
+            ProbabilityOverride::Scale(2.0),
+        )]));
+        let probs = nfc.probabilities();
+
+        assert!((probs[1][1..].iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!((probs[1][1] / probs[1][2] - 2.0 * base[1][1] / base[1][2]).abs() < 1e-9);
+        assert!((probs[1][3] / probs[1][2] - base[1][3] / base[1][2]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_modifier_custom_probabilities_pins_over_one() {
+        let base = make_test_nfc().probabilities();
+        // pirate 14 is in the second arena, so only the first falls back
+        let nfc = make_test_nfc_with_custom_probabilities(HashMap::from([
+            (6, ProbabilityOverride::Pin(0.9)),
+            (11, ProbabilityOverride::Pin(0.9)),
+            (14, ProbabilityOverride::Pin(0.5)),
+        ]));
+        let probs = nfc.probabilities();
+
+        assert_eq!(probs[0], base[0]);
+        assert!((probs[1][1] - 0.5).abs() < 1e-12);
+        assert_eq!(probs[2..], base[2..]);
+    }
+
+    #[test]
+    fn test_modifier_custom_probabilities_pins_add_up_to_one() {
+        let nfc = make_test_nfc_with_custom_probabilities(HashMap::from([
+            (6, ProbabilityOverride::Pin(0.1)),
+            (11, ProbabilityOverride::Pin(0.2)),
+            (4, ProbabilityOverride::Pin(0.7)),
+        ]));
+        let probs = nfc.probabilities();
+
+        assert!((probs[0][1] - 0.1).abs() < 1e-12);
+        assert!((probs[0][2] - 0.2).abs() < 1e-12);
+        assert!((probs[0][3] - 0.7).abs() < 1e-12);
+        assert_eq!(probs[0][4], 0.0);
+    }
+
+    #[test]
+    fn test_modifier_custom_probabilities_arena_scaled_to_zero() {
+        let base = make_test_nfc().probabilities();
+        let nfc = make_test_nfc_with_custom_probabilities(HashMap::from([
+            (6, ProbabilityOverride::Scale(0.0)),
+            (11, ProbabilityOverride::Scale(0.0)),
+            (4, ProbabilityOverride::Scale(0.0)),
+            (3, ProbabilityOverride::Scale(0.0)),
+        ]));
+        let probs = nfc.probabilities();
+
+        assert_eq!(probs, base);
+    }
+
+    #[test]
+    fn test_modifier_custom_probabilities_bets() {
+        let base = make_test_nfc();
+        let pirate_index = base
+            .make_max_ter_bets()
+            .get_indices()
+            .iter()
+            .map(|indices| indices[0])
+            .find(|&index| index != 0)
+            .unwrap();
+        let pirate_id = base.round_data.pirates[0][pirate_index as usize - 1];
+
+        let nfc = make_test_nfc_with_custom_probabilities(HashMap::from([(
+            pirate_id,
+            ProbabilityOverride::Pin(0.0),
+        )]));
+
+        let bets = nfc.make_max_ter_bets();
+        assert!(bets
+            .get_indices()
+            .iter()
+            .all(|indices| indices[0] != pirate_index));
+    }
+
+    #[test]
+    fn test_with_modifier_custom_probabilities_clears_caches() {
+        let mut nfc = make_test_nfc();
+        let base = nfc.probabilities();
+
+        nfc.with_modifier(
+            Modifier::default()
+                .with_custom_probabilities(Some(HashMap::from([(
+                    4,
+                    ProbabilityOverride::Pin(0.6),
+                )])))
+                .unwrap(),
+        );
+
+        assert_ne!(nfc.probabilities(), base);
+        assert!((nfc.probabilities()[0][3] - 0.6).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_modifier_custom_probabilities_invalid() {
+        for (custom_probabilities, error) in [
+            (
+                HashMap::from([(21, ProbabilityOverride::Pin(0.5))]),
+                "Invalid pirate ID",
+            ),
+            (
+                HashMap::from([(1, ProbabilityOverride::Pin(1.5))]),
+                "Invalid probability",
+            ),
+            (
+                HashMap::from([(1, ProbabilityOverride::Scale(-1.0))]),
+                "Invalid probability scale",
+            ),
+            (
+                HashMap::from([(1, ProbabilityOverride::Scale(f64::NAN))]),
+                "Invalid probability scale",
+            ),
+        ] {
+            let result = Modifier::default().with_custom_probabilities(Some(custom_probabilities));
+            assert!(result.unwrap_err().contains(error));
+        }
+    }
+
+    const ARENA_1_FOODS: [u8; 10] = [26, 24, 20, 36, 33, 40, 5, 13, 8, 25];
+
+    fn make_custom_foods_modifier(custom_foods: HashMap<u8, [u8; 10]>) -> Modifier {
+        Modifier::default()
+            .with_custom_foods(Some(custom_foods))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_modifier_custom_foods() {
+        let base = make_test_nfc_logit();
+        let nfc = NeoFoodClub::from_json(
+            ROUND_DATA_JSON,
+            Some(BET_AMOUNT),
+            Some(ProbabilityModel::MultinomialLogitModel),
+            Some(make_custom_foods_modifier(HashMap::from([(
+                0,
+                ARENA_1_FOODS,
+            )]))),
+        );
+
+        assert_eq!(nfc.foods(), base.foods());
+        assert_eq!(nfc.custom_foods().unwrap()[0], ARENA_1_FOODS);
+        assert_eq!(nfc.custom_foods().unwrap()[1..], base.foods().unwrap()[1..]);
+        assert_eq!(
+            nfc.get_arenas().get_arena(0).unwrap().foods,
+            Some(ARENA_1_FOODS)
+        );
+
+        let probs = nfc.probabilities();
+        let base_probs = base.probabilities();
+        assert_ne!(probs[0], base_probs[0]);
+        assert_eq!(probs[1..], base_probs[1..]);
+
+        // the original model doesn't use foods
+        let original = make_test_nfc_with_modifier(make_custom_foods_modifier(HashMap::from([(
+            0,
+            ARENA_1_FOODS,
+        )])));
+        assert_eq!(original.probabilities(), make_test_nfc().probabilities());
+    }
+
+    #[test]
+    fn test_with_modifier_custom_foods_clears_caches() {
+        let arena_pfas = |nfc: &NeoFoodClub| {
+            nfc.get_arenas()
+                .get_arena(0)
+                .unwrap()
+                .pirates
+                .iter()
+                .map(|p| p.pfa)
+                .collect_vec()
+        };
+
+        let mut nfc = make_test_nfc_logit();
+        let base_probs = nfc.probabilities();
+        let base_pfas = arena_pfas(&nfc);
+
+        nfc.with_modifier(make_custom_foods_modifier(HashMap::from([(
+            0,
+            ARENA_1_FOODS,
+        )])));
+        assert_ne!(nfc.probabilities(), base_probs);
+        assert_ne!(arena_pfas(&nfc), base_pfas);
+
+        nfc.with_modifier(Modifier::default());
+        assert_eq!(nfc.custom_foods(), nfc.foods());
+        assert_eq!(nfc.probabilities(), base_probs);
+    }
+
+    #[test]
+    fn test_modifier_custom_foods_without_round_foods() {
+        let make_nfc = |custom_foods: HashMap<u8, [u8; 10]>| {
+            let mut round_data: RoundData = serde_json::from_str(ROUND_DATA_JSON).unwrap();
+            round_data.foods = None;
+            NeoFoodClub::new(
+                round_data,
+                None,
+                None,
+                Some(make_custom_foods_modifier(custom_foods)),
+            )
+        };
+
+        let nfc = make_nfc(HashMap::from([(0, ARENA_1_FOODS)]));
+        assert_eq!(nfc.custom_foods(), None);
+
+        let nfc = make_nfc((0..5).map(|arena| (arena, ARENA_1_FOODS)).collect());
+        assert_eq!(nfc.custom_foods(), Some([ARENA_1_FOODS; 5]));
+        assert!(nfc.get_arenas().get_arena(4).unwrap().pirates[0]
+            .pfa
+            .is_some());
+    }
+
+    #[test]
+    fn test_modifier_custom_foods_invalid() {
+        let result =
+            Modifier::default().with_custom_foods(Some(HashMap::from([(5, ARENA_1_FOODS)])));
+        assert!(result.unwrap_err().contains("Invalid arena index"));
+
+        let mut foods = ARENA_1_FOODS;
+        foods[3] = 41;
+        let result = Modifier::default().with_custom_foods(Some(HashMap::from([(0, foods)])));
+        assert_eq!(result.unwrap_err(), "Invalid food ID, need 1-40, got 41");
+    }
+
+    #[test]
+    fn test_odds_sensitivity_points() {
+        let nfc = make_test_nfc();
+        let sensitivity = nfc.odds_sensitivity(None);
+
+        let expected_points: usize = nfc.custom_odds()[..]
+            .iter()
+            .flat_map(|arena| arena[1..].iter())
+            .map(|&odds| {
+                [-2, -1, 1, 2]
+                    .iter()
+                    .filter(|&&step| (2..=13).contains(&(odds as i8 + step)))
+                    .count()
+            })
+            .sum();
+        assert_eq!(sensitivity.points.len(), expected_points);
+
+        assert!(sensitivity.points.iter().all(|point| {
+            (2..=13).contains(&point.new_odds) && [-2, -1, 1, 2].contains(&point.difference())
+        }));
+
+        let max_ter = nfc.make_max_ter_bets();
+        assert_eq!(sensitivity.expected_return, max_ter.expected_return(&nfc));
+    }
+
+    #[test]
+    fn test_odds_sensitivity_matches_custom_odds() {
+        let nfc = make_test_nfc();
+        let bets = nfc.make_bustproof_bets().unwrap();
+        let sensitivity = nfc.odds_sensitivity(Some(&bets));
+
+        assert_eq!(sensitivity.expected_return, bets.expected_return(&nfc));
+        assert_eq!(sensitivity.net_expected, bets.net_expected(&nfc));
+        assert_eq!(sensitivity.bust_probability, 0.0);
+
+        let max_ter_binaries = |nfc: &NeoFoodClub| {
+            nfc.make_max_ter_bets()
+                .get_binaries()
+                .into_iter()
+                .sorted()
+                .collect_vec()
+        };
+        let base_max_ter = max_ter_binaries(&nfc);
+
+        for point in sensitivity.points.iter().step_by(7) {
+            let moved_nfc = make_test_nfc_with_modifier(
+                Modifier::new(
+                    0,
+                    Some(HashMap::from([(point.pirate.id, point.new_odds)])),
+                    None,
+                )
+                .unwrap(),
+            );
+            let mut moved_bets = Bets::from_binaries(&moved_nfc, bets.get_binaries());
+            moved_bets.bet_amounts = bets.bet_amounts.clone();
+
+            assert_eq!(
+                point.expected_return,
+                moved_bets.expected_return(&moved_nfc)
+            );
+            assert_eq!(point.net_expected, moved_bets.net_expected(&moved_nfc));
+            assert_eq!(
+                point.changes_max_ter,
+                max_ter_binaries(&moved_nfc) != base_max_ter
+            );
+        }
+
+        assert!(sensitivity.max_ter_changes().count() > 0);
+        assert!(sensitivity.table().contains("→"));
+    }
+
+    #[test]
+    fn test_custom_time_wraps_to_next_day() {
+        let start = DateTime::parse_from_rfc3339("2023-05-05T23:14:57+00:00")
+            .unwrap()
+            .to_utc();
+
+        // 16:14:57 NST is the start; a minute before wraps to the next day
+        let before = NaiveTime::from_hms_opt(16, 13, 57).unwrap();
+        let after = NaiveTime::from_hms_opt(16, 15, 57).unwrap();
+
+        assert_eq!(
+            CustomTime::Nst(before).resolve(start),
+            start + TimeDelta::days(1) - TimeDelta::minutes(1)
+        );
+        assert_eq!(
+            CustomTime::Nst(after).resolve(start),
+            start + TimeDelta::minutes(1)
+        );
+    }
+
+    #[test]
+    fn test_custom_time_fall_back() {
+        // clocks fall back at 2am NST on 2023-11-05, so 1:30am happens twice
+        let start = DateTime::parse_from_rfc3339("2023-11-04T23:00:00-07:00")
+            .unwrap()
+            .to_utc();
+
+        let resolved = CustomTime::Nst(NaiveTime::from_hms_opt(1, 30, 0).unwrap()).resolve(start);
+
+        assert_eq!(
+            resolved,
+            DateTime::parse_from_rfc3339("2023-11-05T01:30:00-07:00").unwrap()
+        );
+
+        // the round is 25 hours long, closing at 11pm NST the next day
+        assert_eq!(
+            CustomTime::BeforeClose(TimeDelta::hours(1)).resolve(start),
+            DateTime::parse_from_rfc3339("2023-11-05T22:00:00-08:00").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_custom_time_spring_forward() {
+        // clocks spring forward at 2am NST on 2023-03-12, so 2:30am never happens
+        let start = DateTime::parse_from_rfc3339("2023-03-11T23:00:00-08:00")
+            .unwrap()
+            .to_utc();
+
+        let resolved = CustomTime::Nst(NaiveTime::from_hms_opt(2, 30, 0).unwrap()).resolve(start);
+
+        assert_eq!(
+            resolved,
+            DateTime::parse_from_rfc3339("2023-03-12T03:30:00-07:00").unwrap()
+        );
+
+        // the round is 23 hours long, closing at 11pm NST the next day
+        assert_eq!(
+            CustomTime::BeforeClose(TimeDelta::hours(1)).resolve(start),
+            DateTime::parse_from_rfc3339("2023-03-12T22:00:00-07:00").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_logit() {
+        let nfc = make_test_nfc_logit();
+        let bets = nfc.make_best_gambit_bets();
+
+        assert!(bets.is_gambit());
+    }
+
+    #[test]
+    fn test_last_change_with_timezones() {
+        let nfc = make_test_nfc();
+
+        assert_eq!(
+            nfc.last_change_nst().unwrap().to_string(),
+            "2023-05-06 12:21:01 PDT"
+        );
+
+        assert_eq!(
+            nfc.last_change_utc().unwrap().to_string(),
+            "2023-05-06 19:21:01 UTC"
+        );
+    }
+
+    #[test]
+    fn test_timestamp_with_timezones() {
+        let nfc = make_test_nfc();
+
+        assert_eq!(
+            nfc.timestamp_nst().unwrap().to_string(),
+            "2023-05-06 16:14:20 PDT"
+        );
+
+        assert_eq!(
+            nfc.timestamp_utc().unwrap().to_string(),
+            "2023-05-06 23:14:20 UTC"
+        );
+    }
+
+    #[test]
+    fn test_start_with_timezones() {
+        let nfc = make_test_nfc();
+
+        assert_eq!(
+            nfc.start_nst().unwrap().to_string(),
+            "2023-05-05 16:14:57 PDT"
+        );
+
+        assert_eq!(
+            nfc.start_utc().unwrap().to_string(),
+            "2023-05-05 23:14:57 UTC"
+        );
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let nfc = make_test_nfc();
+
+        assert_eq!(
+            nfc.timestamp().as_ref().unwrap(),
+            "2023-05-06T23:14:20+00:00"
+        );
+    }
+
+    #[test]
+    fn test_last_change() {
+        let nfc = make_test_nfc();
+
+        assert_eq!(
+            nfc.last_change().as_ref().unwrap(),
+            "2023-05-06T19:21:01+00:00"
+        );
+    }
+
+    #[test]
+    fn test_opening_odds() {
+        let nfc = make_test_nfc();
+
+        assert_eq!(
+            nfc.opening_odds(),
+            [
+                [1, 11, 3, 2, 4],
+                [1, 13, 2, 5, 13],
+                [1, 13, 2, 5, 2],
+                [1, 2, 8, 5, 5],
+                [1, 13, 3, 2, 4]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pirates() {
+        let nfc = make_test_nfc();
+
+        assert_eq!(
+            nfc.pirates(),
+            [
+                [6, 11, 4, 3],
+                [14, 15, 2, 9],
+                [10, 16, 18, 20],
+                [1, 12, 13, 5],
+                [8, 19, 17, 7]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_modified() {
+        let nfc = make_test_nfc();
+
+        let mut custom_odds = HashMap::<u8, u8>::new();
+        custom_odds.insert(1, 13);
+
+        let modifier = Modifier::new(
+            ModifierFlags::EMPTY.bits(),
+            Some(custom_odds.clone()),
+            NaiveTime::from_hms_opt(12, 0, 0),
+        )
+        .unwrap();
+
+        let modified_nfc = nfc.copy(None, Some(modifier));
+
+        assert!(modified_nfc.modified());
+
+        assert_ne!(modified_nfc.custom_odds(), *modified_nfc.current_odds());
+
+        assert_eq!(modified_nfc.modifier.custom_odds, Some(custom_odds));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let nfc = make_test_nfc();
+
+        let json = nfc.to_json();
+
+        let new_nfc = NeoFoodClub::from_json(&json, None, None, None);
+
+        assert_eq!(new_nfc.round(), nfc.round());
+        assert!(new_nfc.modifier.is_empty());
+    }
+
+    #[test]
+    fn test_modifier_copy() {
+        let mut custom_odds = HashMap::<u8, u8>::new();
+        custom_odds.insert(1, 13);
+
+        let modifier = Modifier::new(ModifierFlags::EMPTY.bits(), Some(custom_odds), None).unwrap();
+
+        let new_modifier = modifier.copy();
+
+        assert_eq!(modifier, new_modifier);
+    }
+
+    #[test]
+    fn test_odds_change_data() {
+        let nfc = make_test_nfc();
+
+        let changes = nfc.changes().as_ref().unwrap();
+        let odds_change = changes.first().unwrap();
+
+        assert_eq!(odds_change.pirate(&nfc).id, 2);
+        assert_eq!(odds_change.arena(), "Lagoon");
+    }
+
+    #[test]
+    fn test_make_url_all_data() {
+        let nfc = make_test_nfc();
+
+        let bets = nfc.make_bustproof_bets().unwrap();
+
+        let url = nfc.make_url(Some(&bets), true, true);
+
+        assert!(url.contains("winners"));
+        assert!(url.contains("timestamp"));
+    }
+
+    #[test]
+    fn test_make_url_all_data_round_trip() {
+        let nfc = make_test_nfc_logit();
+
+        let mut bets = nfc.make_max_ter_bets();
+        bets.set_bet_amounts(&Some(BetAmounts::Amounts(vec![
+            Some(50),
+            None,
+            Some(8000),
+            Some(1234),
+            Some(50),
+            Some(50),
+            Some(999),
+            Some(8000),
+            None,
+            Some(4000),
+        ])))
+        .unwrap();
+
+        let url = nfc.make_url(Some(&bets), true, true);
+        for param in ["foods=", "start=", "lastChange=", "changes="] {
+            assert!(url.contains(param), "{param} missing from {url}");
+        }
+
+        let (round_tripped, round_tripped_bets) = NeoFoodClub::from_url_with_bets(
+            &url,
+            Some(BET_AMOUNT),
+            Some(ProbabilityModel::MultinomialLogitModel),
+            None,
+        );
+        let round_tripped_bets = round_tripped_bets.unwrap();
+
+        assert_eq!(round_tripped.round_data.foods, nfc.round_data.foods);
+        assert_eq!(round_tripped.round_data.start, nfc.round_data.start);
+        assert_eq!(
+            round_tripped.round_data.lastChange,
+            nfc.round_data.lastChange
+        );
+        assert_eq!(
+            round_tripped.round_data.changes.as_ref().map(Vec::len),
+            Some(15)
+        );
+        assert_eq!(round_tripped.winners(), nfc.winners());
+        assert_eq!(round_tripped.probabilities(), nfc.probabilities());
+        assert_eq!(round_tripped.modifier, nfc.modifier);
+
+        assert_eq!(round_tripped_bets.get_binaries(), bets.get_binaries());
+        assert_eq!(round_tripped_bets.bet_amounts, bets.bet_amounts);
+        assert_eq!(
+            round_tripped.make_url(Some(&round_tripped_bets), true, true),
+            url
+        );
+    }
+
+    #[test]
+    fn test_from_url_with_bets_no_bets() {
+        let (nfc, bets) = NeoFoodClub::from_url_with_bets(ROUND_DATA_URL, None, None, None);
+
+        assert_eq!(nfc.round(), 7956);
+        assert!(bets.is_none());
+    }
+
+    #[test]
+    fn test_bets_from_url() {
+        let nfc = make_test_nfc();
+        let bets = nfc.make_bustproof_bets().unwrap();
+
+        let url = nfc.make_url(Some(&bets), true, false);
+        let url_bets = nfc.bets_from_url(&url).unwrap().unwrap();
+
+        assert_eq!(url_bets.bets.get_binaries(), bets.get_binaries());
+        assert_eq!(url_bets.bets.bet_amounts, bets.bet_amounts);
+        assert!(url_bets.warnings.is_empty());
+
+        assert!(nfc
+            .bets_from_url("https://neofood.club/#round=8765")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_bets_from_url_too_many_bets() {
+        let nfc = make_test_nfc();
+        let cc_nfc = make_test_nfc_with_modifier(
+            Modifier::new(ModifierFlags::CHARITY_CORNER.bits(), None, None).unwrap(),
+        );
+        let bets = cc_nfc.make_max_ter_bets();
+        assert_eq!(bets.len(), 15);
+
+        // the 15-bet path allows it, whatever the modifier
+        let url = cc_nfc.make_url(Some(&bets), false, false);
+        assert!(url.starts_with("/15/"));
+        let url_bets = nfc.bets_from_url(&url).unwrap().unwrap();
+        assert_eq!(url_bets.bets.len(), 15);
+        assert!(url_bets.warnings.is_empty());
+
+        let url = url.replacen("/15/", "/", 1);
+        let url_bets = nfc.bets_from_url(&url).unwrap().unwrap();
+        assert_eq!(url_bets.bets.len(), 15);
+        assert_eq!(
+            url_bets.warnings,
+            vec!["URL has 15 bets, but only 10 can be placed.".to_string()]
+        );
+
+        let url_bets = cc_nfc.bets_from_url(&url).unwrap().unwrap();
+        assert!(url_bets.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_bets_from_url_errors() {
+        let nfc = make_test_nfc();
+
+        assert!(nfc.bets_from_url("https://neofood.club/").is_err());
+        assert_eq!(
+            nfc.bets_from_url("/#round=7956&b=gmgmgmgmgm").unwrap_err(),
+            "URL is for round 7956, not round 8765."
+        );
+        assert!(nfc.bets_from_url("/#round=8765&b=g!mg").is_err());
+        assert!(nfc
+            .bets_from_url("/#round=8765&b=gmgmgmgmgm&a=EmxCoK")
+            .unwrap_err()
+            .starts_with("Bet amounts must be the same length"));
+    }
+
+    #[test]
+    fn test_make_url_all_data_no_bets() {
+        let nfc = make_test_nfc();
+
+        let url = nfc.make_url(None, true, false);
+
+        assert_eq!(url, "https://neofood.club/#round=8765");
+    }
+
+    #[test]
+    fn test_make_url_modifier_round_trip() {
+        let custom_odds = HashMap::from([(2, 13), (8, 2)]);
+        let modifier = Modifier::new(
+            (ModifierFlags::GENERAL | ModifierFlags::REVERSE).bits(),
+            Some(custom_odds),
+            Some(NaiveTime::from_hms_opt(12, 0, 0).unwrap()),
+        )
+        .unwrap();
+
+        let nfc = make_test_nfc_from_url_with_modifier(modifier.clone());
+        let bets = nfc.make_max_ter_bets();
+
+        let url = nfc.make_url(Some(&bets), true, true);
+        assert!(url.contains("&modifier=5"));
+
+        let round_tripped = NeoFoodClub::from_url(&url, Some(BET_AMOUNT), None, None);
+
+        assert_eq!(round_tripped.modifier, modifier);
+        assert_eq!(round_tripped.custom_odds(), nfc.custom_odds());
+        assert_eq!(
+            round_tripped.make_max_ter_bets().get_binaries(),
+            bets.get_binaries()
+        );
+        assert_eq!(round_tripped.make_url(Some(&bets), true, true), url);
+    }
+
+    #[test]
+    fn test_make_url_custom_time_round_trip() {
+        let nfc = make_test_nfc_with_custom_time(CustomTime::Nst(
+            NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+        ));
+        assert_eq!(nfc.changes().as_ref().map(Vec::len), Some(4));
+
+        let url = nfc.make_url(None, true, true);
+        let round_tripped = NeoFoodClub::from_url(&url, Some(BET_AMOUNT), None, None);
+
+        assert_eq!(round_tripped.all_changes().as_ref().map(Vec::len), Some(15));
+        assert_eq!(round_tripped.changes().as_ref().map(Vec::len), Some(4));
+        assert_eq!(round_tripped.custom_odds(), nfc.custom_odds());
+        assert_eq!(round_tripped.make_url(None, true, true), url);
+    }
+
+    #[test]
+    fn test_make_url_custom_probabilities_round_trip() {
+        let modifier = Modifier::default()
+            .with_custom_probabilities(Some(HashMap::from([
+                (2, ProbabilityOverride::Pin(0.6)),
+                (7, ProbabilityOverride::Scale(1.5)),
+            ])))
+            .unwrap();
+
+        let nfc = make_test_nfc_from_url_with_modifier(modifier.clone());
+
+        let url = nfc.make_url(None, true, true);
+        assert!(url.contains("&customProbabilities="));
+
+        let round_tripped = NeoFoodClub::from_url(&url, Some(BET_AMOUNT), None, None);
+
+        assert_eq!(round_tripped.modifier, modifier);
+        assert_eq!(round_tripped.probabilities(), nfc.probabilities());
+        assert_eq!(round_tripped.make_url(None, true, true), url);
+    }
+
+    #[test]
+    fn test_make_url_custom_foods_round_trip() {
+        let modifier =
+            make_custom_foods_modifier(HashMap::from([(0, ARENA_1_FOODS), (3, ARENA_1_FOODS)]));
+
+        let nfc = NeoFoodClub::from_url(
+            ROUND_DATA_URL,
+            Some(BET_AMOUNT),
+            Some(ProbabilityModel::MultinomialLogitModel),
+            Some(modifier.clone()),
+        );
+
+        let url = nfc.make_url(None, true, true);
+        assert!(url.contains("&customFoods="));
+
+        let round_tripped = NeoFoodClub::from_url(
+            &url,
+            Some(BET_AMOUNT),
+            Some(ProbabilityModel::MultinomialLogitModel),
+            None,
+        );
+
+        assert_eq!(round_tripped.modifier, modifier);
+        assert_eq!(round_tripped.probabilities(), nfc.probabilities());
+        assert_eq!(round_tripped.make_url(None, true, true), url);
+    }
+
+    #[test]
+    fn test_make_url_modifier_charity_corner() {
+        let modifier = Modifier::new(
+            (ModifierFlags::CHARITY_CORNER | ModifierFlags::OPENING_ODDS).bits(),
+            None,
+            None,
+        )
+        .unwrap()
+        .with_custom_time(Some(CustomTime::BeforeClose(TimeDelta::minutes(30))));
+
+        let nfc = make_test_nfc_from_url_with_modifier(modifier.clone());
+
+        let url = nfc.make_url(None, false, true);
+        assert!(url.starts_with("/15/#"));
+        assert!(url.contains("&modifier=2&"));
+
+        let round_tripped = NeoFoodClub::from_url(&url, None, None, None);
+        assert_eq!(round_tripped.modifier, modifier);
+    }
+
+    #[test]
+    fn test_from_url_modifier_argument_takes_precedence() {
+        let url =
+            format!("{ROUND_DATA_URL}&modifier=1&customOdds={{\"2\":13}}&customTime=start%2B60");
+
+        let nfc = NeoFoodClub::from_url(
+            &url,
+            None,
+            None,
+            Some(
+                Modifier::new(
+                    ModifierFlags::REVERSE.bits(),
+                    Some(HashMap::from([(2, 5)])),
+                    None,
+                )
+                .unwrap(),
+            ),
+        );
+
+        // the flags conflict, so only the given ones are used
+        assert!(!nfc.modifier.is_general());
+        assert!(nfc.modifier.is_reverse());
+        assert_eq!(nfc.modifier.custom_odds, Some(HashMap::from([(2, 5)])));
+        assert_eq!(
+            nfc.modifier.custom_time,
+            Some(CustomTime::AfterStart(TimeDelta::seconds(60)))
+        );
+    }
+
+    #[test]
+    fn test_from_url_modifier_argument_overrides_flags() {
+        let url = format!(
+            "/15/#{}&modifier=3",
+            ROUND_DATA_URL.split('#').nth(1).unwrap()
+        );
+
+        let nfc = NeoFoodClub::from_url(&url, None, None, None);
+        assert!(nfc.modifier.is_general());
+        assert!(nfc.modifier.is_charity_corner());
+
+        let nfc = NeoFoodClub::from_url(
+            &url,
+            None,
+            None,
+            Some(Modifier::new(ModifierFlags::OPENING_ODDS.bits(), None, None).unwrap()),
+        );
+        assert_eq!(
+            nfc.modifier.value,
+            (ModifierFlags::OPENING_ODDS | ModifierFlags::CHARITY_CORNER).bits()
+        );
+
+        // an empty modifier clears the URL's flags, but not the /15/ path's
+        let nfc = NeoFoodClub::from_url(&url, None, None, Some(Modifier::default()));
+        assert_eq!(nfc.modifier.value, ModifierFlags::CHARITY_CORNER.bits());
+    }
+
+    #[test]
+    fn test_from_url_charity_corner_path_with_modifier() {
+        let url = format!("/15/#{}", ROUND_DATA_URL.split('#').nth(1).unwrap());
+        let modifier = Modifier::new(ModifierFlags::REVERSE.bits(), None, None).unwrap();
+
+        let nfc = NeoFoodClub::from_url(&url, None, None, Some(modifier.clone()));
+        assert!(nfc.modifier.is_charity_corner());
+        assert!(nfc.modifier.is_reverse());
+        assert_eq!(nfc.max_amount_of_bets(), 15);
+
+        let nfc = NeoFoodClub::from_url(ROUND_DATA_URL, None, None, Some(modifier));
+        assert!(!nfc.modifier.is_charity_corner());
+    }
+
+    #[test]
+    fn test_custom_time_string_round_trip() {
+        let custom_times = [
+            CustomTime::Nst(NaiveTime::from_hms_opt(9, 5, 30).unwrap()),
+            CustomTime::At(
+                DateTime::parse_from_rfc3339("2023-05-06T00:21:43+00:00")
+                    .unwrap()
+                    .to_utc(),
+            ),
+            CustomTime::AfterStart(TimeDelta::minutes(70)),
+            CustomTime::BeforeClose(TimeDelta::hours(2)),
+        ];
+
+        for custom_time in custom_times {
+            assert_eq!(
+                custom_time.to_string().parse::<CustomTime>(),
+                Ok(custom_time)
+            );
+        }
+
+        assert_eq!(custom_times[2].to_string(), "start+4200");
+        assert_eq!("start 4200".parse::<CustomTime>(), Ok(custom_times[2]));
+        assert_eq!(
+            "2023-05-06T00:21:43 00:00".parse::<CustomTime>(),
+            Ok(custom_times[1])
+        );
+        assert!("noon".parse::<CustomTime>().is_err());
+        assert!("close-soon".parse::<CustomTime>().is_err());
+    }
+
+    #[test]
+    fn test_from_url_custom_time_unescaped_plus() {
+        let url = format!("{ROUND_DATA_URL}&customTime=start+3600");
+
+        let nfc = NeoFoodClub::from_url(&url, None, None, None);
+
+        assert_eq!(
+            nfc.modifier.custom_time,
+            Some(CustomTime::AfterStart(TimeDelta::hours(1)))
+        );
+    }
+
+    #[test]
+    fn test_make_all_max_ter_bets() {
+        let nfc = make_test_nfc();
+
+        let bets = nfc.make_all_max_ter_bets();
+
+        assert_eq!(bets.len(), 3124);
+    }
+
+    #[test]
+    fn test_is_outdated_lock() {
+        let nfc = make_test_nfc();
+
+        // our test data is from 2023-05-06
+        // this is probably always going to be true
+        assert!(nfc.is_outdated_lock());
+    }
+
+    #[test]
+    fn test_bets_table() {
+        let nfc = make_test_nfc();
+
+        let bets = nfc.make_bustproof_bets().unwrap();
+
+        let table = bets.table(&nfc);
+
+        assert_eq!(
+            table,
+            r#"
++---+-----------+----------+----------+---------+---------+
+| # | Shipwreck | Lagoon   | Treasure | Hidden  | Harpoon |
++=========================================================+
+| 1 |           | Sproggie |          |         |         |
+|---+-----------+----------+----------+---------+---------|
+| 2 |           | Fairfax  |          |         |         |
+|---+-----------+----------+----------+---------+---------|
+| 3 |           | Stuff    |          |         |         |
+|---+-----------+----------+----------+---------+---------|
+| 4 |           | Gooblah  |          | Dan     |         |
+|---+-----------+----------+----------+---------+---------|
+| 5 |           | Gooblah  |          | Stripey |         |
+|---+-----------+----------+----------+---------+---------|
+| 6 |           | Gooblah  |          | Ned     |         |
+|---+-----------+----------+----------+---------+---------|
+| 7 |           | Gooblah  |          | Edmund  |         |
++---+-----------+----------+----------+---------+---------+
+"#
+            .trim()
+        )
+    }
+
+    #[test]
+    fn test_bets_stats_table() {
+        let nfc = make_test_nfc();
+
+        let bets = nfc.make_bustproof_bets().unwrap();
+
+        let table = bets.stats_table(&nfc);
+
+        assert_eq!(
+            table,
+            r#"
++---+------+---------+---------+----------+---------+--------+---------+-----------+----------+----------+---------+---------+
+| # | Odds |    ER   |    BE   |  Margin  |    NE   | MaxBet |   Hex   | Shipwreck |  Lagoon  | Treasure |  Hidden | Harpoon |
++============================================================================================================================+
+| 1 |   7  | 1.283:1 | 14.286% |  +4.048% | 1295.12 | 142858 | 0x02000 |           | Sproggie |          |         |         |
+|---+------+---------+---------+----------+---------+--------+---------+-----------+----------+----------+---------+---------|
+| 2 |  13  | 0.650:1 |  7.692% |  -2.692% | -861.35 |  76924 | 0x08000 |           |  Fairfax |          |         |         |
+|---+------+---------+---------+----------+---------+--------+---------+-----------+----------+----------+---------+---------|
+| 3 |  13  | 0.650:1 |  7.692% |  -2.692% | -861.35 |  76924 | 0x01000 |           |   Stuff  |          |         |         |
+|---+------+---------+---------+----------+---------+--------+---------+-----------+----------+----------+---------+---------|
+| 4 |   4  | 1.477:1 | 25.000% | +11.928% | 3817.04 | 250000 | 0x04080 |           |  Gooblah |          |   Dan   |         |
+|---+------+---------+---------+----------+---------+--------+---------+-----------+----------+----------+---------+---------|
+| 5 |  20  | 1.692:1 |  5.000% |  +3.461% | 1107.41 |  50000 | 0x04040 |           |  Gooblah |          | Stripey |         |
+|---+------+---------+---------+----------+---------+--------+---------+-----------+----------+----------+---------+---------|
+| 6 |  12  | 1.577:1 |  8.333% |  +4.806% | 1537.39 |  83334 | 0x04020 |           |  Gooblah |          |   Ned   |         |
+|---+------+---------+---------+----------+---------+--------+---------+-----------+----------+----------+---------+---------|
+| 7 |  12  | 1.577:1 |  8.333% |  +4.806% | 1537.39 |  83334 | 0x04010 |           |  Gooblah |          |  Edmund |         |
++---+------+---------+---------+----------+---------+--------+---------+-----------+----------+----------+---------+---------+"#
+            .trim()
+        )
+    }
+
+    #[test]
+    fn test_break_even_probabilities() {
+        let nfc = make_test_nfc();
+        let data = nfc.round_dict_data();
+
+        for index in [0, 123, 3123] {
+            assert_eq!(
+                data.break_even_probability(index),
+                1.0 / data.odds[index] as f64
+            );
+            // a bet is +EV exactly when its probability is above break-even
+            assert_eq!(data.break_even_margin(index) > 0.0, data.ers[index] > 1.0);
+        }
+
+        let mut bets = nfc.make_bustproof_bets().unwrap();
+        let margins = bets.break_even_margins(&nfc);
+        for (margin, er) in margins.iter().zip(bets.expected_return_list(&nfc)) {
+            assert_eq!(*margin > 0.0, er > 1.0);
+        }
+
+        // locked odds move the break-even point, but not the model's probabilities
+        bets.set_locked_odds(&nfc, Some(vec![2; bets.len()]))
+            .unwrap();
+        assert!(bets
+            .break_even_probabilities(&nfc)
+            .iter()
+            .all(|&break_even| break_even == 0.5));
+        assert!(bets
+            .break_even_margins(&nfc)
+            .iter()
+            .zip(margins.iter())
+            .all(|(locked, margin)| locked < margin));
+    }
+
+    #[test]
+    fn test_pirate_break_even_margin() {
+        let nfc = make_test_nfc();
+        let probabilities = nfc.probabilities();
+
+        for arena in nfc.get_arenas().arenas.iter() {
+            for pirate in arena.pirates.iter() {
+                let probability = probabilities[pirate.arena_id as usize][pirate.index as usize];
+
+                assert_eq!(
+                    pirate.break_even_probability(&nfc),
+                    1.0 / pirate.current_odds as f64
+                );
+                assert_eq!(
+                    pirate.break_even_margin(&nfc),
+                    probability - pirate.break_even_probability(&nfc)
+                );
+                assert_eq!(
+                    pirate.break_even_margin(&nfc) > 0.0,
+                    probability * pirate.current_odds as f64 > 1.0
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_dominating_sub_bets() {
+        let nfc = make_test_nfc();
+        let data = nfc.round_dict_data();
+        let dominating = dominance::find_dominating_sub_bets(data, BET_AMOUNT);
+
+        assert!(dominating.iter().any(Option::is_some));
+
+        // each bet is compared at its own stake, capped to what it takes to hit 1 million
+        let stake = |i: usize| data.maxbets[i].clamp(BET_AMOUNT_MIN, BET_AMOUNT);
+        let payout = |i: usize| (data.odds[i] * stake(i)).min(1_000_000);
+        let net_expected = |i: usize| stake(i) as f64 * data.ers[i] - stake(i) as f64;
+
+        for (index, sub_index) in dominating.iter().enumerate() {
+            let bin = data.bins[index];
+            let sub_bets =
+                (0..3124).filter(|&i| data.bins[i] != bin && data.bins[i] & bin == data.bins[i]);
+            let better = sub_bets
+                .filter(|&i| payout(i) >= payout(index) && net_expected(i) > net_expected(index))
+                .collect_vec();
+
+            match sub_index {
+                Some(sub_index) => {
+                    assert!(better.contains(sub_index));
+                    assert!(better
+                        .iter()
+                        .all(|&i| data.probs[i] <= data.probs[*sub_index]));
+                }
+                None => assert!(better.is_empty()),
+            }
+        }
+
+        // single pirates have no sub-bets
+        assert!(dominating
+            .iter()
+            .zip(data.bins.iter())
+            .all(|(sub_index, bin)| bin.count_ones() > 1 || sub_index.is_none()));
+    }
+
+    #[test]
+    fn test_dominated_bets() {
+        let nfc = make_test_nfc();
+        let data = nfc.round_dict_data();
+        let dominating = dominance::find_dominating_sub_bets(data, BET_AMOUNT);
+
+        let dominated_index = (0..3124).find(|&i| dominating[i].is_some()).unwrap();
+        let sub_index = dominating[dominated_index].unwrap();
+        let fine_index = (0..3124).find(|&i| dominating[i].is_none()).unwrap();
+
+        let mut bets = Bets::new(&nfc, vec![dominated_index, fine_index, dominated_index]);
+        // without any amounts, only the duplicate is flagged
+        let no_amount_nfc = NeoFoodClub::from_json(ROUND_DATA_JSON, None, None, None);
+        let dominated = bets.dominated_bets(&no_amount_nfc);
+        assert_eq!(dominated.len(), 1);
+        assert_eq!(dominated[0].duplicate_of, Some(0));
+        assert_eq!(dominated[0].dominated_by, None);
+
+        bets.set_bet_amounts(&Some(BetAmounts::AllSame(BET_AMOUNT)))
+            .unwrap();
+        let dominated = bets.dominated_bets(&nfc);
+
+        assert_eq!(dominated.len(), 2);
+        assert_eq!(
+            dominated[0],
+            DominatedBet {
+                bet_index: 0,
+                duplicate_of: None,
+                dominated_by: Some(sub_index),
+                replacement: Some(sub_index),
+            }
+        );
+        assert_eq!(dominated[1].bet_index, 2);
+        assert_eq!(dominated[1].duplicate_of, Some(0));
+        assert_eq!(dominated[1].dominated_by, Some(sub_index));
+
+        // the sub-bet is already suggested, so the next best max TER bet is
+        let replacement = dominated[1].replacement.unwrap();
+        assert!(![dominated_index, fine_index, sub_index].contains(&replacement));
+        assert!(dominating[replacement].is_none());
+
+        assert!(nfc.make_max_ter_bets().dominated_bets(&nfc).is_empty());
+    }
+
+    #[test]
+    fn test_prune_dominated() {
+        let mut nfc = make_test_nfc();
+        let unpruned = nfc.make_all_max_ter_bets();
+
+        nfc.set_prune_dominated(true);
+        let pruned = nfc.make_all_max_ter_bets();
+        let dominating = dominance::find_dominating_sub_bets(nfc.round_dict_data(), BET_AMOUNT);
+
+        assert!(pruned.len() < unpruned.len());
+        assert_eq!(
+            pruned.len(),
+            dominating
+                .iter()
+                .filter(|sub_index| sub_index.is_none())
+                .count()
+        );
+        assert!(pruned
+            .array_indices
+            .iter()
+            .all(|&index| dominating[index].is_none()));
+
+        // the order of what's left is kept
+        let kept = unpruned
+            .array_indices
+            .iter()
+            .copied()
+            .filter(|&index| dominating[index].is_none())
+            .collect_vec();
+        assert_eq!(pruned.array_indices, kept);
+
+        // gambits still have full-arena bets to pick from
+        assert!(!nfc.make_best_gambit_bets().is_empty());
+
+        // other generators still rank every bet
+        let mut unpruned_nfc = nfc.clone();
+        unpruned_nfc.set_prune_dominated(false);
+        assert_eq!(
+            nfc.make_tenbet_bets(0x88800).unwrap().get_binaries(),
+            unpruned_nfc
+                .make_tenbet_bets(0x88800)
+                .unwrap()
+                .get_binaries()
+        );
+        assert_eq!(
+            nfc.make_bustproof_bets().map(|bets| bets.get_binaries()),
+            unpruned_nfc
+                .make_bustproof_bets()
+                .map(|bets| bets.get_binaries())
+        );
+
+        // without a bet amount, nothing is pruned
+        nfc.set_bet_amount(None);
+        assert_eq!(nfc.make_all_max_ter_bets().len(), 3124);
+    }
+
+    #[test]
+    fn test_prune_dominated_keeps_net_expected() {
+        for bet_amount in [1000, BET_AMOUNT, 20000, BET_AMOUNT_MAX] {
+            for mut nfc in [
+                NeoFoodClub::from_json(ROUND_DATA_JSON, Some(bet_amount), None, None),
+                NeoFoodClub::from_url(ROUND_DATA_URL, Some(bet_amount), None, None),
+            ] {
+                let unpruned = nfc.make_max_ter_bets().net_expected(&nfc);
+
+                nfc.set_prune_dominated(true);
+                let pruned = nfc.make_max_ter_bets().net_expected(&nfc);
+
+                assert!(pruned >= unpruned, "{pruned} < {unpruned} at {bet_amount}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_bets_stats_table_reverse_mer() {
+        let nfc = make_test_nfc_with_modifier(
+            Modifier::new(ModifierFlags::REVERSE.bits(), None, None).unwrap(),
+        );
+
+        let bets = nfc.make_max_ter_bets();
+
+        let table = bets.stats_table(&nfc);
+
+        assert_eq!(
+            table,
+            r#"
++----+------+---------+--------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------+
+|  # | Odds |    ER   |   BE   |  Margin |    NE    | MaxBet |   Hex   | Shipwreck |  Lagoon |  Treasure  | Hidden |  Harpoon  |
++==============================================================================================================================+
+|  1 |  78  | 0.336:1 | 1.282% | -0.851% | -5309.00 |  12821 | 0x10108 |   Orvinn  |         |  Tailhook  |        |   Puffo   |
+|----+------+---------+--------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
+|  2 |  78  | 0.336:1 | 1.282% | -0.851% | -5309.00 |  12821 | 0x10408 |   Orvinn  |         | Franchisco |        |   Puffo   |
+|----+------+---------+--------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
+|  3 |  78  | 0.336:1 | 1.282% | -0.851% | -5309.00 |  12821 | 0x11400 |   Orvinn  |  Stuff  | Franchisco |        |           |
+|----+------+---------+--------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
+|  4 |  78  | 0.336:1 | 1.282% | -0.851% | -5309.00 |  12821 | 0x11100 |   Orvinn  |  Stuff  |  Tailhook  |        |           |
+|----+------+---------+--------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
+|  5 |  78  | 0.336:1 | 1.282% | -0.851% | -5309.00 |  12821 | 0x18400 |   Orvinn  | Fairfax | Franchisco |        |           |
+|----+------+---------+--------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
+|  6 |  78  | 0.336:1 | 1.282% | -0.851% | -5309.00 |  12821 | 0x18100 |   Orvinn  | Fairfax |  Tailhook  |        |           |
+|----+------+---------+--------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
+|  7 |  104 | 0.342:1 | 0.962% | -0.632% | -5262.09 |  9616  | 0x21402 |   Lucky   |  Stuff  | Franchisco |        | Federismo |
+|----+------+---------+--------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
+|  8 |  104 | 0.342:1 | 0.962% | -0.632% | -5262.09 |  9616  | 0x28402 |   Lucky   | Fairfax | Franchisco |        | Federismo |
+|----+------+---------+--------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
+|  9 |  104 | 0.342:1 | 0.962% | -0.632% | -5262.09 |  9616  | 0x28102 |   Lucky   | Fairfax |  Tailhook  |        | Federismo |
+|----+------+---------+--------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
+| 10 |  104 | 0.342:1 | 0.962% | -0.632% | -5262.09 |  9616  | 0x21102 |   Lucky   |  Stuff  |  Tailhook  |        | Federismo |
++----+------+---------+--------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------+"#
+            .trim()
+        )
+    }
+
+    #[test]
+    fn test_is_guaranteed_win_no_bet_amounts() {
+        let nfc = NeoFoodClub::from_json(ROUND_DATA_JSON, None, None, None);
+
+        let bets = nfc.make_bustproof_bets().unwrap();
+
+        assert!(!bets.is_guaranteed_win(&nfc));
+    }
+
+    #[test]
+    fn test_imake_bets_from_array_indices() {
+        let nfc = NeoFoodClub::from_json(ROUND_DATA_JSON, None, None, None);
+
+        let bets = nfc.make_bets_from_array_indices(vec![1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(bets.len(), 6);
+    }
+
+    #[test]
+    fn test_set_bet_amounts_error() {
+        let nfc = make_test_nfc();
+
+        let mut bets = nfc.make_max_ter_bets();
+        let result = bets.set_bet_amounts(&Some(BetAmounts::Amounts(vec![None; 1])));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_is_guaranteed_win_none_bet_amounts() {
+        let nfc = make_test_nfc();
+
+        let mut bets = nfc.make_bustproof_bets().unwrap();
+        bets.set_bet_amounts(&Some(BetAmounts::Amounts(vec![
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(1000),
+        ])))
+        .unwrap();
+
+        assert!(!bets.is_guaranteed_win(&nfc));
+    }
+
+    #[test]
+    fn test_is_guaranteed_win_negative_bet_amounts() {
+        let nfc = make_test_nfc();
+
+        let mut bets = nfc.make_max_ter_bets();
+        bets.set_bet_amounts(&Some(BetAmounts::Amounts(vec![Some(0); 10])))
+            .unwrap();
+
+        assert!(!bets.is_guaranteed_win(&nfc));
+    }
+
+    fn make_all_thirteens_nfc() -> NeoFoodClub {
+        let custom_odds: HashMap<u8, u8> = (1..=20).map(|pirate_id| (pirate_id, 13)).collect();
+        let modifier =
+            Modifier::new(ModifierFlags::empty().bits(), Some(custom_odds), None).unwrap();
+
+        NeoFoodClub::from_json(ROUND_DATA_JSON, Some(BET_AMOUNT_MAX), None, Some(modifier))
+    }
+
+    #[test]
+    fn test_get_win_np_extreme_odds() {
+        let nfc = make_all_thirteens_nfc();
+        let mut bets = nfc.make_bets_from_indices(vec![nfc.winners()]);
+        bets.set_bet_amounts(&Some(BetAmounts::AllSame(BET_AMOUNT_MAX)))
+            .unwrap();
+
+        assert_eq!(bets.odds_values(&nfc), vec![371_293]);
+        assert_eq!(nfc.get_win_units(&bets), 371_293);
+        assert_eq!(nfc.get_win_np(&bets), 1_000_000);
+    }
+
+    #[test]
+    fn test_is_guaranteed_win_extreme_odds() {
+        let nfc = make_all_thirteens_nfc();
+        let mut bets = nfc.make_bets_from_indices(math::winner_combinations());
+        bets.set_bet_amounts(&Some(BetAmounts::AllSame(BET_AMOUNT_MAX)))
+            .unwrap();
+
+        assert!(bets.is_bustproof());
+        assert!(bets.is_guaranteed_win(&nfc));
+    }
+
+    #[test]
+    fn test_bustproof_bet_amounts_at_max() {
+        let nfc = NeoFoodClub::from_json(ROUND_DATA_JSON, Some(BET_AMOUNT_MAX), None, None);
+        let bets = nfc.make_bustproof_bets().unwrap();
+
+        let odds = bets.odds_values(&nfc);
+        let lowest = *odds.iter().min().unwrap() as u64;
+
+        for (amount, odd) in bets.bet_amounts.unwrap().iter().zip(odds) {
+            let expected = BET_AMOUNT_MAX as u64 * lowest / odd as u64;
+            assert_eq!(amount.unwrap() as u64, expected);
+        }
+    }
+
+    #[test]
+    fn test_locked_odds() {
+        let nfc = make_test_nfc();
+        let mut bets = nfc.make_bustproof_bets().unwrap();
+
+        let doubled: Vec<u32> = bets.odds_values(&nfc).iter().map(|o| o * 2).collect();
+        let er = bets.expected_return(&nfc);
+        let np_mean = bets.np_odds(&nfc).unwrap().mean_payout();
+
+        bets.set_locked_odds(&nfc, Some(doubled.clone())).unwrap();
+
+        assert_eq!(bets.odds_values(&nfc), doubled);
+        assert!((bets.expected_return(&nfc) - er * 2.0).abs() < 1e-9);
+        assert!((bets.odds.mean() - er * 2.0).abs() < 1e-9);
+        assert_eq!(nfc.get_win_units(&bets), 40);
+        assert_eq!(nfc.get_win_np(&bets), 64_000);
+        assert!((bets.np_odds(&nfc).unwrap().mean_payout() - np_mean * 2.0).abs() < 1e-6);
+
+        bets.set_locked_odds(&nfc, None).unwrap();
+
+        assert_eq!(nfc.get_win_units(&bets), 20);
+        assert!((bets.expected_return(&nfc) - er).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_locked_odds_errors() {
+        let nfc = make_test_nfc();
+        let mut bets = nfc.make_max_ter_bets();
+
+        assert!(bets.set_locked_odds(&nfc, Some(vec![2; 3])).is_err());
+        assert!(bets.set_locked_odds(&nfc, Some(vec![0; 10])).is_err());
+        assert!(bets.locked_odds().is_none());
+    }
+
+    #[test]
+    fn test_lock_odds_at() {
+        let nfc = make_test_nfc();
+        let mut bets = nfc.make_max_ter_bets();
+        let current_odds = bets.odds_values(&nfc);
+
+        // before any changes, the bets are locked at their opening odds
+        bets.lock_odds_at(&nfc, nfc.start_utc().unwrap()).unwrap();
+        let opening = NeoFoodClub::from_json(
+            ROUND_DATA_JSON,
+            Some(BET_AMOUNT),
+            None,
+            Some(Modifier::new(ModifierFlags::OPENING_ODDS.bits(), None, None).unwrap()),
+        );
+        let opening_bets = opening.make_bets_from_binaries(bets.get_binaries());
+        assert_eq!(bets.odds_values(&nfc), opening_bets.odds_values(&opening));
+        assert!((bets.expected_return(&nfc) - opening_bets.expected_return(&opening)).abs() < 1e-9);
+
+        // after the last change, they match the current odds
+        bets.lock_odds_at(&nfc, nfc.last_change_utc().unwrap())
+            .unwrap();
+        assert_eq!(bets.odds_values(&nfc), current_odds);
+    }
+
+    #[test]
+    fn test_lock_odds_at_no_changes() {
+        let nfc = make_test_nfc_from_url();
+        let mut bets = nfc.make_max_ter_bets();
+
+        // without changes, the odds never moved from the opening odds
+        bets.lock_odds_at(&nfc, chrono::Utc::now()).unwrap();
+        let opening = nfc.round_data.openingOdds;
+        let expected: Vec<u32> = bets
+            .get_indices()
+            .iter()
+            .map(|indices| {
+                indices
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &index)| index > 0)
+                    .map(|(arena, &index)| opening[arena][index as usize] as u32)
+                    .product()
+            })
+            .collect();
+        assert_eq!(bets.locked_odds(), &Some(expected));
+    }
+
+    #[test]
+    fn test_lock_odds_at_ignores_custom_time() {
+        let nfc = make_test_nfc_with_custom_time(CustomTime::Nst(
+            NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+        ));
+        let mut bets = nfc.make_bets_from_binaries(vec![pirate_binary(4, 0)]);
+
+        // Lagoon's fourth pirate went back to 3:1 at 07:29 UTC, after the custom time
+        let timestamp = DateTime::parse_from_rfc3339("2023-05-06T20:00:00+00:00")
+            .unwrap()
+            .to_utc();
+        bets.lock_odds_at(&nfc, timestamp).unwrap();
+        assert_eq!(bets.locked_odds(), &Some(vec![3]));
+    }
+
+    #[test]
+    fn test_invalid_gambit() {
+        let nfc = make_test_nfc();
+
+        let bets = nfc.make_bets_from_binaries(vec![0x1]);
+
+        assert!(!bets.is_gambit());
+    }
+
+    #[test]
+    fn test_most_likely_winner() {
+        let nfc = make_test_nfc();
+
+        let bets = nfc.make_bets_from_binaries(vec![0x1, 0x10]);
+
+        assert_eq!(bets.odds.most_likely_winner().value, 4);
+    }
+
+    #[test]
+    fn test_best_odds() {
+        let nfc = make_test_nfc();
+
+        let bets = nfc.make_bets_from_binaries(vec![0x1, 0x11]);
+
+        assert_eq!(bets.odds.best().value, 28);
+    }
+
+    #[test]
+    fn test_partial_rate() {
+        let nfc = make_test_nfc();
+
+        let bets = nfc.make_bets_from_binaries(vec![0x1, 0x20, 0x01248, 0x01244, 0x01240]);
+
+        let rate = bets.odds.partial_rate();
+
+        // 0.18350651041666674 but it can differ slightly on different systems
+        assert!(rate < 0.19);
+        assert!(rate > 0.18);
+    }
+
+    #[test]
+    fn test_odds_mean_is_expected_return() {
+        let nfc = make_test_nfc();
+        let bets = nfc.make_max_ter_bets();
 
-        assert_eq!(
-            table,
-            r#"
-+----+------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------+
-|  # | Odds |    ER   |    NE    | MaxBet |   Hex   | Shipwreck |  Lagoon |  Treasure  | Hidden |  Harpoon  |
-+===========================================================================================================+
-|  1 |  78  | 0.336:1 | -5309.00 |  12821 | 0x10108 |   Orvinn  |         |  Tailhook  |        |   Puffo   |
-|----+------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
-|  2 |  78  | 0.336:1 | -5309.00 |  12821 | 0x10408 |   Orvinn  |         | Franchisco |        |   Puffo   |
-|----+------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
-|  3 |  78  | 0.336:1 | -5309.00 |  12821 | 0x11400 |   Orvinn  |  Stuff  | Franchisco |        |           |
-|----+------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
-|  4 |  78  | 0.336:1 | -5309.00 |  12821 | 0x11100 |   Orvinn  |  Stuff  |  Tailhook  |        |           |
-|----+------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
-|  5 |  78  | 0.336:1 | -5309.00 |  12821 | 0x18400 |   Orvinn  | Fairfax | Franchisco |        |           |
-|----+------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
-|  6 |  78  | 0.336:1 | -5309.00 |  12821 | 0x18100 |   Orvinn  | Fairfax |  Tailhook  |        |           |
-|----+------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
-|  7 |  104 | 0.342:1 | -5262.09 |  9616  | 0x21402 |   Lucky   |  Stuff  | Franchisco |        | Federismo |
-|----+------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
-|  8 |  104 | 0.342:1 | -5262.09 |  9616  | 0x28402 |   Lucky   | Fairfax | Franchisco |        | Federismo |
-|----+------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
-|  9 |  104 | 0.342:1 | -5262.09 |  9616  | 0x28102 |   Lucky   | Fairfax |  Tailhook  |        | Federismo |
-|----+------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------|
-| 10 |  104 | 0.342:1 | -5262.09 |  9616  | 0x21102 |   Lucky   |  Stuff  |  Tailhook  |        | Federismo |
-+----+------+---------+----------+--------+---------+-----------+---------+------------+--------+-----------+"#
-            .trim()
-        )
+        assert!((bets.odds.mean() - bets.expected_return(&nfc)).abs() < 1e-9);
     }
 
     #[test]
-    fn test_is_guaranteed_win_no_bet_amounts() {
-        let nfc = NeoFoodClub::from_json(ROUND_DATA_JSON, None, None, None);
+    fn test_odds_variance() {
+        let nfc = make_test_nfc();
+        let bets = nfc.make_bets_from_binaries(vec![0x1, 0x10]);
+
+        let mean = bets.odds.mean();
+        let second_moment: f64 = bets
+            .odds
+            .chances()
+            .iter()
+            .map(|c| (c.value as f64).powi(2) * c.probability)
+            .sum();
+
+        assert!((bets.odds.variance() - (second_moment - mean * mean)).abs() < 1e-9);
+        assert!((bets.odds.std_dev() - bets.odds.variance().sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_odds_quantiles() {
+        let nfc = make_test_nfc();
+        let bets = nfc.make_bets_from_binaries(vec![0x1, 0x10]);
+        let chances = bets.odds.chances();
+
+        assert_eq!(bets.odds.quantile(0.0), chances.first().unwrap().value);
+        assert_eq!(bets.odds.quantile(1.0), chances.last().unwrap().value);
+        assert_eq!(bets.odds.quantile(-1.0), bets.odds.quantile(0.0));
+        assert_eq!(bets.odds.median(), bets.odds.quantile(0.5));
+
+        // the bust probability is more than half here, so the median busts
+        assert!(bets.odds.bust().unwrap().probability > 0.5);
+        assert_eq!(bets.odds.median(), 0);
+    }
+
+    #[test]
+    fn test_odds_probability_at_least() {
+        let nfc = make_test_nfc();
+        let bets = nfc.make_max_ter_bets();
+
+        assert!((bets.odds.probability_at_least(0) - 1.0).abs() < 1e-9);
+        assert_eq!(bets.odds.probability_at_least(u32::MAX), 0.0);
+
+        let bust = bets.odds.bust().map_or(0.0, |c| c.probability);
+        assert!((bets.odds.probability_at_least(1) - (1.0 - bust)).abs() < 1e-9);
+
+        for chance in bets.odds.chances() {
+            let expected: f64 = bets
+                .odds
+                .chances()
+                .iter()
+                .filter(|c| c.value >= chance.value)
+                .map(|c| c.probability)
+                .sum();
+            assert!((bets.odds.probability_at_least(chance.value) - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_odds_probability_of_profit() {
+        let nfc = make_test_nfc();
+        let bets = nfc.make_max_ter_bets();
+
+        let expected: f64 = bets
+            .odds
+            .chances()
+            .iter()
+            .filter(|c| c.value > bets.len() as u32)
+            .map(|c| c.probability)
+            .sum();
 
+        assert!((bets.odds.probability_of_profit() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_odds_expected_shortfall() {
+        let nfc = make_test_nfc();
         let bets = nfc.make_bustproof_bets().unwrap();
+        let odds = &bets.odds;
 
-        assert!(!bets.is_guaranteed_win(&nfc));
+        let worst = odds.chances()[0].value as f64;
+        assert_eq!(odds.expected_shortfall(0.0), worst);
+        assert!(
+            (odds.expected_shortfall(odds.chances()[0].probability / 2.0) - worst).abs() < 1e-9
+        );
+        assert!((odds.expected_shortfall(1.0) - odds.mean()).abs() < 1e-9);
+        assert!(odds.expected_shortfall(0.1) <= odds.expected_shortfall(0.5));
     }
 
     #[test]
-    fn test_imake_bets_from_array_indices() {
-        let nfc = NeoFoodClub::from_json(ROUND_DATA_JSON, None, None, None);
+    fn test_np_odds_matches_units_when_uncapped() {
+        let nfc = NeoFoodClub::from_json(ROUND_DATA_JSON, Some(100), None, None);
+        let bets = nfc.make_max_ter_bets();
 
-        let bets = nfc.make_bets_from_array_indices(vec![1, 2, 3, 4, 5, 6]);
+        let np_odds = bets.np_odds(&nfc).unwrap();
 
-        assert_eq!(bets.len(), 6);
+        assert_eq!(np_odds.total_stake(), 100 * bets.len() as u32);
+        assert_eq!(np_odds.chances().len(), bets.odds.chances().len());
+
+        for (np, units) in np_odds.chances().iter().zip(bets.odds.chances()) {
+            assert_eq!(np.payout, units.value * 100);
+            assert_eq!(np.profit, np.payout as i64 - np_odds.total_stake() as i64);
+            assert!((np.probability - units.probability).abs() < 1e-12);
+        }
+
+        assert!((np_odds.mean_payout() - bets.odds.mean() * 100.0).abs() < 1e-6);
+        assert!((np_odds.mean_profit() - bets.net_expected(&nfc)).abs() < 1e-6);
     }
 
     #[test]
-    fn test_set_bet_amounts_error() {
+    fn test_np_odds_respects_payout_cap() {
         let nfc = make_test_nfc();
+        let mut bets = nfc.make_bets_from_binaries(vec![0x88888]);
+        bets.set_bet_amounts(&Some(BetAmounts::AllSame(BET_AMOUNT_MAX)))
+            .unwrap();
 
-        let mut bets = nfc.make_max_ter_bets();
-        let result = bets.set_bet_amounts(&Some(BetAmounts::Amounts(vec![None; 1])));
+        let np_odds = bets.np_odds(&nfc).unwrap();
 
-        assert!(result.is_err());
+        assert_eq!(np_odds.best().payout, 1_000_000);
+        assert_eq!(np_odds.best().profit, 1_000_000 - BET_AMOUNT_MAX as i64);
+        assert_eq!(np_odds.bust().unwrap().profit, -(BET_AMOUNT_MAX as i64));
     }
 
     #[test]
-    fn test_is_guaranteed_win_none_bet_amounts() {
+    fn test_np_odds_varied_amounts() {
         let nfc = make_test_nfc();
+        let bets = nfc.make_bustproof_bets().unwrap();
 
-        let mut bets = nfc.make_bustproof_bets().unwrap();
-        bets.set_bet_amounts(&Some(BetAmounts::Amounts(vec![
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            Some(1000),
-        ])))
-        .unwrap();
+        let np_odds = bets.np_odds(&nfc).unwrap();
 
-        assert!(!bets.is_guaranteed_win(&nfc));
+        assert!(np_odds.bust().is_none());
+        assert!(np_odds.chances().iter().all(|c| c.profit > 0));
+        assert!((np_odds.probability_of_profit() - 1.0).abs() < 1e-9);
+        assert!((np_odds.chances().last().unwrap().cumulative - 1.0).abs() < 1e-9);
     }
 
     #[test]
-    fn test_is_guaranteed_win_negative_bet_amounts() {
+    fn test_np_odds_no_bet_amounts() {
+        let nfc = NeoFoodClub::from_json(ROUND_DATA_JSON, None, None, None);
+        let bets = nfc.make_max_ter_bets();
+
+        assert!(bets.np_odds(&nfc).is_none());
+    }
+
+    #[test]
+    fn test_compare_bets_against_itself() {
         let nfc = make_test_nfc();
+        let bets = nfc.make_max_ter_bets();
 
-        let mut bets = nfc.make_max_ter_bets();
-        bets.set_bet_amounts(&Some(BetAmounts::Amounts(vec![Some(0); 10])))
-            .unwrap();
+        let comparison = nfc.compare_bets(&bets, &bets);
 
-        assert!(!bets.is_guaranteed_win(&nfc));
+        assert!(comparison.in_np());
+        assert_eq!(comparison.outcomes().len(), 1024);
+        assert_eq!(comparison.shared_bets(), bets.bet_binaries.as_slice());
+        assert!((comparison.probability_tie() - 1.0).abs() < 1e-9);
+        assert_eq!(comparison.probability_a_wins(), 0.0);
+        assert!((comparison.correlation().unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(comparison.er_gap(), 0.0);
     }
 
     #[test]
-    fn test_invalid_gambit() {
+    fn test_compare_bets_probabilities() {
+        let nfc = NeoFoodClub::from_json(ROUND_DATA_JSON, None, None, None);
+        let a = nfc.make_max_ter_bets();
+        let b = nfc.make_gambit_bets(0x12481);
+
+        let comparison = nfc.compare_bets(&a, &b);
+
+        assert!(!comparison.in_np());
+
+        let total = comparison.probability_a_wins()
+            + comparison.probability_b_wins()
+            + comparison.probability_tie();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        let mean_a: f64 = comparison
+            .outcomes()
+            .iter()
+            .map(|o| o.a as f64 * o.probability)
+            .sum();
+        assert!((mean_a - a.expected_return(&nfc)).abs() < 1e-6);
+
+        let gap = a.expected_return(&nfc) - b.expected_return(&nfc);
+        assert!((comparison.er_gap() - gap).abs() < 1e-12);
+
+        let correlation = comparison.correlation().unwrap();
+        assert!((-1.0..=1.0).contains(&correlation));
+
+        for bin in comparison.shared_bets() {
+            assert!(a.bet_binaries.contains(bin) && b.bet_binaries.contains(bin));
+        }
+    }
+
+    #[test]
+    fn test_evaluate_outcome_actual_winners() {
         let nfc = make_test_nfc();
+        let bets = nfc.make_max_ter_bets();
 
-        let bets = nfc.make_bets_from_binaries(vec![0x1]);
+        let outcome = nfc.evaluate_outcome(&bets, nfc.winners());
 
-        assert!(!bets.is_gambit());
+        assert_eq!(outcome.units, nfc.get_win_units(&bets));
+        assert_eq!(outcome.np, Some(nfc.get_win_np(&bets)));
+        assert_eq!(outcome.hits.len(), bets.len());
     }
 
     #[test]
-    fn test_most_likely_winner() {
+    fn test_evaluate_outcome_hypothetical_winners() {
         let nfc = make_test_nfc();
+        let bets = nfc.make_bets_from_indices(vec![[1, 0, 0, 0, 0], [2, 0, 0, 0, 0]]);
 
-        let bets = nfc.make_bets_from_binaries(vec![0x1, 0x10]);
+        let outcome = nfc.evaluate_outcome(&bets, [2, 1, 1, 1, 1]);
 
-        assert_eq!(bets.odds.most_likely_winner().value, 4);
+        assert_eq!(outcome.hits, vec![false, true]);
+        assert_eq!(outcome.hit_count(), 1);
+        assert_eq!(outcome.units, nfc.current_odds()[0][2] as u32);
+        assert_eq!(outcome.np, Some(outcome.units * BET_AMOUNT));
+
+        // the round itself is untouched
+        assert_eq!(nfc.winners(), [3, 2, 3, 2, 2]);
     }
 
     #[test]
-    fn test_best_odds() {
+    #[should_panic(expected = "Winners must all be 1-4.")]
+    fn test_evaluate_outcome_invalid_winners() {
         let nfc = make_test_nfc();
+        let bets = nfc.make_max_ter_bets();
 
-        let bets = nfc.make_bets_from_binaries(vec![0x1, 0x11]);
+        nfc.evaluate_outcome(&bets, [0, 0, 0, 0, 0]);
+    }
 
-        assert_eq!(bets.odds.best().value, 28);
+    #[test]
+    fn test_payoff_table() {
+        let nfc = make_test_nfc();
+        let bets = nfc.make_max_ter_bets();
+
+        let table = nfc.payoff_table(&bets);
+
+        assert_eq!(table.len(), 1024);
+        assert!(table.iter().map(|o| o.winners).all_unique());
+
+        let total: f64 = table.iter().map(|o| o.probability).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        let mean_units: f64 = table.iter().map(|o| o.units as f64 * o.probability).sum();
+        assert!((mean_units - bets.expected_return(&nfc)).abs() < 1e-6);
+
+        let actual = table.iter().find(|o| o.winners == nfc.winners()).unwrap();
+        assert_eq!(actual.units, nfc.get_win_units(&bets));
     }
 
     #[test]
-    fn test_partial_rate() {
+    fn test_bet_results() {
         let nfc = make_test_nfc();
+        let bets = nfc.make_bustproof_bets().unwrap();
 
-        let bets = nfc.make_bets_from_binaries(vec![0x1, 0x20, 0x01248, 0x01244, 0x01240]);
+        let results = nfc.bet_results(&bets).unwrap();
 
-        let rate = bets.odds.partial_rate();
+        assert_eq!(results.len(), bets.len());
+        assert_eq!(results.iter().filter(|r| r.hit).count(), 1);
+        assert_eq!(
+            results.iter().map(|r| r.payout).sum::<u32>(),
+            nfc.get_win_np(&bets)
+        );
+        assert!(results.iter().all(|r| r.lost_to_cap() == 0));
+        assert!(results.iter().filter(|r| !r.hit).all(|r| r.profit() < 0));
+    }
 
-        // 0.18350651041666674 but it can differ slightly on different systems
-        assert!(rate < 0.19);
-        assert!(rate > 0.18);
+    #[test]
+    fn test_bet_results_lost_to_cap() {
+        let nfc = make_test_nfc();
+        let mut bets = nfc.make_bets_from_indices(vec![nfc.winners()]);
+        bets.set_bet_amounts(&Some(BetAmounts::AllSame(BET_AMOUNT_MAX)))
+            .unwrap();
+
+        let result = &nfc.bet_results(&bets).unwrap()[0];
+
+        assert!(result.hit);
+        assert_eq!(result.payout, 1_000_000);
+        assert_eq!(
+            result.raw_payout,
+            result.odds as u64 * BET_AMOUNT_MAX as u64
+        );
+        assert_eq!(result.lost_to_cap(), result.raw_payout - 1_000_000);
+        assert_eq!(result.profit(), 1_000_000 - BET_AMOUNT_MAX as i64);
+    }
+
+    #[test]
+    fn test_bet_results_no_winners() {
+        let nfc = NeoFoodClub::from_url(ROUND_DATA_URL_NO_WINNERS, None, None, None);
+        let bets = nfc.make_max_ter_bets();
+
+        assert!(nfc.bet_results(&bets).is_none());
+        assert!(bets.results_table(&nfc).is_none());
+    }
+
+    #[test]
+    fn test_bets_results_table() {
+        let nfc = make_test_nfc();
+
+        let bets = nfc.make_bustproof_bets().unwrap();
+
+        let table = bets.results_table(&nfc).unwrap();
+
+        assert_eq!(
+            table,
+            r#"
++---+-----+------+---------+---------+----------+---------+--------+-------+---------+-----------+----------+----------+---------+---------+
+| # | Hit | Odds |    ER   |    BE   |  Margin  |    NE   | MaxBet |  Won  |   Hex   | Shipwreck |  Lagoon  | Treasure |  Hidden | Harpoon |
++==========================================================================================================================================+
+| 1 |     |   7  | 1.283:1 | 14.286% |  +4.048% | 1295.12 | 142858 |       | 0x02000 |           | Sproggie |          |         |         |
+|---+-----+------+---------+---------+----------+---------+--------+-------+---------+-----------+----------+----------+---------+---------|
+| 2 |     |  13  | 0.650:1 |  7.692% |  -2.692% | -861.35 |  76924 |       | 0x08000 |           |  Fairfax |          |         |         |
+|---+-----+------+---------+---------+----------+---------+--------+-------+---------+-----------+----------+----------+---------+---------|
+| 3 |     |  13  | 0.650:1 |  7.692% |  -2.692% | -861.35 |  76924 |       | 0x01000 |           |   Stuff  |          |         |         |
+|---+-----+------+---------+---------+----------+---------+--------+-------+---------+-----------+----------+----------+---------+---------|
+| 4 |     |   4  | 1.477:1 | 25.000% | +11.928% | 3817.04 | 250000 |       | 0x04080 |           |  Gooblah |          |   Dan   |         |
+|---+-----+------+---------+---------+----------+---------+--------+-------+---------+-----------+----------+----------+---------+---------|
+| 5 |  ✓  |  20  | 1.692:1 |  5.000% |  +3.461% | 1107.41 |  50000 | 32000 | 0x04040 |           |  Gooblah |          | Stripey |         |
+|---+-----+------+---------+---------+----------+---------+--------+-------+---------+-----------+----------+----------+---------+---------|
+| 6 |     |  12  | 1.577:1 |  8.333% |  +4.806% | 1537.39 |  83334 |       | 0x04020 |           |  Gooblah |          |   Ned   |         |
+|---+-----+------+---------+---------+----------+---------+--------+-------+---------+-----------+----------+----------+---------+---------|
+| 7 |     |  12  | 1.577:1 |  8.333% |  +4.806% | 1537.39 |  83334 |       | 0x04010 |           |  Gooblah |          |  Edmund |         |
++---+-----+------+---------+---------+----------+---------+--------+-------+---------+-----------+----------+----------+---------+---------+
+Hits: 1/7
+Units won: 20
+Staked: 24425 NP
+Won: 32000 NP
+Profit: 7575 NP"#
+                .trim()
+        )
     }
 
     #[test]
@@ -1688,6 +3787,341 @@ mod tests {
         assert!(!chances.is_empty());
     }
 
+    #[test]
+    fn test_sample_winners() {
+        let nfc = make_test_nfc();
+        let mut rng = StdRng::seed_from_u64(8765);
+
+        for _ in 0..100 {
+            let winners = nfc.sample_winners(&mut rng);
+            assert!(winners.iter().all(|w| (1..=4).contains(w)));
+        }
+    }
+
+    #[test]
+    fn test_sample_winners_seeded() {
+        let nfc = make_test_nfc();
+
+        let first: Vec<[u8; 5]> = {
+            let mut rng = StdRng::seed_from_u64(1);
+            (0..10).map(|_| nfc.sample_winners(&mut rng)).collect()
+        };
+        let second: Vec<[u8; 5]> = {
+            let mut rng = StdRng::seed_from_u64(1);
+            (0..10).map(|_| nfc.sample_winners(&mut rng)).collect()
+        };
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_simulate_matches_odds() {
+        let nfc = make_test_nfc();
+        let bets = nfc.make_max_ter_bets();
+        let mut rng = StdRng::seed_from_u64(8765);
+
+        let simulation = nfc.simulate(&bets, 20_000, &mut rng);
+
+        let expected_units: f64 = bets
+            .odds
+            .chances()
+            .iter()
+            .map(|c| c.value as f64 * c.probability)
+            .sum();
+
+        assert_eq!(simulation.rounds, 20_000);
+        assert!((simulation.mean_units() - expected_units).abs() / expected_units < 0.05);
+
+        let expected_bust = bets.odds.bust().map_or(0.0, |c| c.probability);
+        assert!((simulation.bust_rate() - expected_bust).abs() < 0.02);
+
+        let last = simulation.units.last().unwrap();
+        assert!((last.cumulative - 1.0).abs() < 1e-9);
+        assert!(simulation.mean_np() > 0.0);
+    }
+
+    #[test]
+    fn test_simulate_no_bet_amounts() {
+        let nfc = NeoFoodClub::from_json(ROUND_DATA_JSON, None, None, None);
+        let bets = nfc.make_max_ter_bets();
+        let mut rng = StdRng::seed_from_u64(8765);
+
+        let simulation = nfc.simulate(&bets, 100, &mut rng);
+
+        assert!(simulation.np.is_empty());
+        assert_eq!(simulation.mean_np(), 0.0);
+        assert!(!simulation.units.is_empty());
+    }
+
+    #[test]
+    fn test_kelly_fraction() {
+        let nfc = make_test_nfc();
+
+        let fraction = nfc.make_max_ter_bets().odds.kelly_fraction();
+        assert!(fraction > 0.0 && fraction < 1.0);
+
+        // with the same amount on every bet, the neopoint distribution agrees
+        let mut bets = nfc.make_max_ter_bets();
+        bets.set_bet_amounts(&Some(BetAmounts::AllSame(BET_AMOUNT_MIN)))
+            .unwrap();
+        let np_fraction = bets.np_odds(&nfc).unwrap().kelly_fraction();
+        assert!((np_fraction - fraction).abs() < 1e-9);
+
+        // but capped amounts weigh the bets differently
+        let max_nfc = NeoFoodClub::from_json(ROUND_DATA_JSON, Some(BET_AMOUNT_MAX), None, None);
+        let bets = max_nfc.make_max_ter_bets();
+        let np_fraction = bets.np_odds(&max_nfc).unwrap().kelly_fraction();
+        assert!(np_fraction > 0.0 && np_fraction < 1.0);
+        assert_ne!(np_fraction, bets.odds.kelly_fraction());
+
+        let reverse = make_test_nfc_with_modifier(
+            Modifier::new(ModifierFlags::REVERSE.bits(), None, None).unwrap(),
+        );
+        assert_eq!(reverse.make_max_ter_bets().odds.kelly_fraction(), 0.0);
+    }
+
+    #[test]
+    fn test_bankroll_fixed() {
+        let nfc = make_test_nfc();
+        let mut rng = StdRng::seed_from_u64(8765);
+
+        let simulation = BankrollSimulation::new(
+            std::slice::from_ref(&nfc),
+            |nfc| Some(nfc.make_max_ter_bets()),
+            BetAmountPolicy::Fixed(BET_AMOUNT),
+            1_000_000,
+            30,
+            200,
+            &mut rng,
+        );
+
+        assert_eq!(simulation.paths.len(), 200);
+        for path in simulation.paths.iter() {
+            assert_eq!(path.bankrolls.len(), 31);
+            assert_eq!(path.bankrolls[0], 1_000_000);
+            assert!(path.stopped_at.is_none());
+            assert!(path.max_drawdown <= *path.bankrolls.iter().max().unwrap());
+        }
+        // the set is +EV, so the paths grow by about its mean profit each round
+        let mean_profit = nfc.make_max_ter_bets().np_odds(&nfc).unwrap().mean_profit();
+        let expected = 1_000_000.0 + 30.0 * mean_profit;
+        assert!(simulation.probability_of_ruin() < 0.2);
+        assert!((simulation.mean_final_bankroll() - expected).abs() / expected < 0.2);
+    }
+
+    #[test]
+    fn test_bankroll_fills_amounts_with_policy() {
+        let nfc = make_test_nfc();
+        let bets = nfc.make_max_ter_bets();
+        let hash = bets.bets_hash();
+
+        let simulation = BankrollSimulation::new(
+            std::slice::from_ref(&nfc),
+            |nfc| Bets::from_hash(nfc, &hash).ok(),
+            BetAmountPolicy::Fixed(1000),
+            15_000,
+            1,
+            10,
+            &mut StdRng::seed_from_u64(8765),
+        );
+
+        // 10 bets of 1000 NP fit, so the first round is always played
+        assert_eq!(simulation.probability_of_ruin(), 0.0);
+        for path in simulation.paths.iter() {
+            assert_ne!(path.final_bankroll(), 15_000);
+        }
+    }
+
+    #[test]
+    fn test_bankroll_fixed_is_ruined_below_total_stake() {
+        let nfc = make_test_nfc();
+        // the worst full-arena bets, which return less than they stake on average
+        let data = nfc.round_dict_data();
+        let worst = (0..3124)
+            .filter(|&index| data.bins[index].count_ones() == 5)
+            .sorted_by(|&a, &b| data.ers[a].total_cmp(&data.ers[b]))
+            .take(10)
+            .collect_vec();
+        let mut bets = Bets::new(&nfc, worst);
+        bets.fill_bet_amounts(&nfc);
+        assert!(bets.expected_return(&nfc) < bets.len() as f64);
+        let total_stake = bets
+            .bet_amounts
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|amount| amount.unwrap() as u64)
+            .sum::<u64>();
+
+        // fixed stakes aren't scaled down, so a bankroll short of them is ruined right away
+        let simulation = BankrollSimulation::new(
+            std::slice::from_ref(&nfc),
+            |_| Some(bets.clone()),
+            BetAmountPolicy::Fixed(BET_AMOUNT),
+            total_stake - 1,
+            5,
+            3,
+            &mut StdRng::seed_from_u64(8765),
+        );
+
+        assert_eq!(simulation.probability_of_ruin(), 1.0);
+        for path in simulation.paths.iter() {
+            assert_eq!(path.ruined_at, Some(1));
+            assert_eq!(path.bankrolls, vec![total_stake - 1; 6]);
+        }
+
+        // with enough for two rounds, a path is ruined exactly when it drops below the stake
+        let simulation = BankrollSimulation::new(
+            &[nfc],
+            |_| Some(bets.clone()),
+            BetAmountPolicy::Fixed(BET_AMOUNT),
+            total_stake * 2,
+            20,
+            20,
+            &mut StdRng::seed_from_u64(8765),
+        );
+
+        // the set loses in the long run, so most paths are ruined
+        assert!(simulation.probability_of_ruin() > 0.5);
+        for path in simulation.paths.iter() {
+            let played = path.ruined_at.map_or(20, |ruined_at| ruined_at - 1);
+            assert!(path.bankrolls[..played].iter().all(|&b| b >= total_stake));
+            if let Some(ruined_at) = path.ruined_at {
+                assert!(path.bankrolls[played] < total_stake);
+                assert!(path.bankrolls[ruined_at..]
+                    .iter()
+                    .all(|&b| b == path.bankrolls[played]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_bankroll_pays_locked_odds() {
+        let nfc = make_test_nfc();
+        let mut bets = nfc.make_max_ter_bets();
+        bets.set_locked_odds(&nfc, Some(vec![1; bets.len()]))
+            .unwrap();
+
+        let simulation = BankrollSimulation::new(
+            std::slice::from_ref(&nfc),
+            |_| Some(bets.clone()),
+            BetAmountPolicy::Fixed(BET_AMOUNT),
+            1_000_000,
+            10,
+            10,
+            &mut StdRng::seed_from_u64(8765),
+        );
+
+        // at 1:1, a winning bet only pays its stake back
+        for path in simulation.paths.iter() {
+            assert!(path.bankrolls.iter().all(|&b| b <= 1_000_000));
+        }
+        assert_eq!(simulation.probability_of_doubling(), 0.0);
+    }
+
+    #[test]
+    fn test_bankroll_guaranteed_win_never_draws_down() {
+        let nfc = make_test_nfc();
+        let mut rng = StdRng::seed_from_u64(8765);
+
+        let simulation = BankrollSimulation::new(
+            &[nfc],
+            |nfc| nfc.make_bustproof_bets(),
+            BetAmountPolicy::Fixed(BET_AMOUNT),
+            100_000,
+            10,
+            10,
+            &mut rng,
+        );
+
+        for path in simulation.paths.iter() {
+            assert_eq!(path.max_drawdown, 0);
+            assert!(path.bankrolls.windows(2).all(|w| w[0] < w[1]));
+        }
+        assert_eq!(simulation.mean_max_drawdown(), 0.0);
+    }
+
+    #[test]
+    fn test_bankroll_ruin() {
+        let nfc = make_test_nfc();
+        let mut rng = StdRng::seed_from_u64(8765);
+
+        let simulation = BankrollSimulation::new(
+            &[nfc],
+            |nfc| Some(nfc.make_crazy_bets()),
+            BetAmountPolicy::Fixed(BET_AMOUNT),
+            100,
+            50,
+            20,
+            &mut rng,
+        );
+
+        assert!(simulation.probability_of_ruin() > 0.0);
+        for path in simulation.paths.iter().filter(|p| p.ruined_at.is_some()) {
+            let ruined_at = path.ruined_at.unwrap();
+            assert!(path.bankrolls[ruined_at..]
+                .iter()
+                .all(|&b| b == path.bankrolls[ruined_at]));
+        }
+    }
+
+    #[test]
+    fn test_bankroll_stop_after_bust_streak() {
+        let nfc = make_test_nfc();
+        let mut rng = StdRng::seed_from_u64(8765);
+
+        let simulation = BankrollSimulation::new(
+            &[nfc],
+            |nfc| Some(nfc.make_crazy_bets()),
+            BetAmountPolicy::StopAfterBustStreak {
+                amount: 1000,
+                streak: 1,
+            },
+            1_000_000,
+            10,
+            10,
+            &mut rng,
+        );
+
+        assert!(simulation.paths.iter().any(|p| p.stopped_at.is_some()));
+        for path in simulation.paths.iter() {
+            if let Some(stopped_at) = path.stopped_at {
+                assert!(path.bankrolls[stopped_at..]
+                    .iter()
+                    .all(|&b| b == path.bankrolls[stopped_at]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_bankroll_kelly() {
+        let nfc = make_test_nfc();
+        let mut rng = StdRng::seed_from_u64(8765);
+
+        let simulation = BankrollSimulation::new(
+            &[nfc.clone(), nfc],
+            |nfc| Some(nfc.make_max_ter_bets()),
+            BetAmountPolicy::Kelly(0.5),
+            1_000_000,
+            30,
+            20,
+            &mut rng,
+        );
+
+        // kelly never stakes the whole bankroll
+        assert_eq!(simulation.probability_of_ruin(), 0.0);
+        // the set is +EV, so most paths double within the month
+        assert!(simulation.probability_of_doubling() > 0.5);
+        let mean_time_to_double = simulation.mean_time_to_double().unwrap();
+        assert!((1.0..=30.0).contains(&mean_time_to_double));
+        for path in simulation.paths.iter() {
+            if let Some(doubled_at) = path.doubled_at {
+                assert!(path.bankrolls[doubled_at] >= 2_000_000);
+                assert!(path.bankrolls[..doubled_at].iter().all(|&b| b < 2_000_000));
+            }
+        }
+    }
+
     #[test]
     fn test_round_data_timestamps() {
         let nfc = make_test_nfc();
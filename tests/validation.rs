@@ -29,6 +29,7 @@ fn get_base_round_data() -> RoundData {
         foods: None,
         winners: None,
         customOdds: None,
+        customFoods: None,
         start: None,
         timestamp: None,
         changes: None,